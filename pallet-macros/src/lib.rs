@@ -19,17 +19,26 @@ struct FieldMeta {
     ident: syn::Ident,
     name: String,
     ty: syn::Type,
+    value: proc_macro2::TokenStream,
     opts: proc_macro2::TokenStream,
     is_default_search_field: bool,
+    is_indexed: bool,
+    is_unique: bool,
+    is_sled_index: bool,
+    is_suggest: bool,
 }
 
-fn handle_field(input: &syn::Field) -> Result<Option<FieldMeta>, Box<dyn std::error::Error>> {
+fn handle_field(input: &syn::Field) -> Result<FieldMeta, Box<dyn std::error::Error>> {
     let pallet_path: syn::Path = parse_quote!(pallet);
     let index_field_name_path: syn::Path = parse_quote!(index_field_name);
     let skip_indexing_path: syn::Path = parse_quote!(skip_indexing);
     let index_field_type_path: syn::Path = parse_quote!(index_field_type);
     let index_field_options_path: syn::Path = parse_quote!(index_field_options);
     let default_search_field_path: syn::Path = parse_quote!(default_search_field);
+    let facet_path: syn::Path = parse_quote!(facet);
+    let unique_path: syn::Path = parse_quote!(unique);
+    let sled_index_path: syn::Path = parse_quote!(sled_index);
+    let suggest_path: syn::Path = parse_quote!(suggest);
 
     let ident = input.ident.as_ref().unwrap();
 
@@ -37,6 +46,8 @@ fn handle_field(input: &syn::Field) -> Result<Option<FieldMeta>, Box<dyn std::er
 
     let mut ty = input.ty.clone();
 
+    let mut value = quote!(self.#ident.clone().into());
+
     let mut opts = quote!(std::option::Option::<()>::None);
 
     let l_attrs = input
@@ -54,9 +65,13 @@ fn handle_field(input: &syn::Field) -> Result<Option<FieldMeta>, Box<dyn std::er
             _ => None,
         });
 
-    if l_attrs.clone().any(|x| x.path() == &skip_indexing_path) {
-        return Ok(None);
-    }
+    let is_indexed = !l_attrs.clone().any(|x| x.path() == &skip_indexing_path);
+
+    let is_unique = l_attrs.clone().any(|x| x.path() == &unique_path);
+
+    let is_sled_index = l_attrs.clone().any(|x| x.path() == &sled_index_path);
+
+    let is_suggest = l_attrs.clone().any(|x| x.path() == &suggest_path);
 
     let is_default_search_field = l_attrs.clone().any(|x| x.path() == &default_search_field_path);
 
@@ -76,6 +91,32 @@ fn handle_field(input: &syn::Field) -> Result<Option<FieldMeta>, Box<dyn std::er
         name = index_field_name;
     }
 
+    if l_attrs.clone().any(|x| x.path() == &facet_path) {
+        // Optional and collection fields keep their shape, with each value converted to a `Facet`.
+        let wrapper = match &input.ty {
+            syn::Type::Path(type_path) => {
+                type_path.path.segments.last().map(|segment| segment.ident.to_string())
+            }
+            _ => None,
+        };
+
+        match wrapper.as_deref() {
+            Some("Option") => {
+                ty = parse_quote!(std::option::Option<pallet::search::Facet>);
+                value = quote!(self.#ident.clone().map(pallet::search::Facet::from));
+            }
+            Some("Vec") | Some("HashSet") | Some("BTreeSet") => {
+                ty = parse_quote!(std::vec::Vec<pallet::search::Facet>);
+                value = quote! {
+                    self.#ident.clone().into_iter().map(pallet::search::Facet::from).collect::<std::vec::Vec<_>>()
+                };
+            }
+            _ => {
+                ty = parse_quote!(pallet::search::Facet);
+            }
+        }
+    }
+
     if let Some(user_ty) = l_attrs
         .clone()
         .filter_map(|x| match x {
@@ -90,6 +131,7 @@ fn handle_field(input: &syn::Field) -> Result<Option<FieldMeta>, Box<dyn std::er
         .next()
     {
         ty = user_ty;
+        value = quote!(self.#ident.clone().into());
     }
 
     if let Some(index_fields_options) = l_attrs
@@ -109,7 +151,18 @@ fn handle_field(input: &syn::Field) -> Result<Option<FieldMeta>, Box<dyn std::er
         opts = index_fields_options;
     }
 
-    Ok(Some(FieldMeta { ident: ident.clone(), name, ty, opts, is_default_search_field }))
+    Ok(FieldMeta {
+        ident: ident.clone(),
+        name,
+        ty,
+        value,
+        opts,
+        is_default_search_field,
+        is_indexed,
+        is_unique,
+        is_sled_index,
+        is_suggest,
+    })
 }
 
 fn document_derive_inner(
@@ -125,6 +178,8 @@ fn document_derive_inner(
     let name = &input.ident;
     let pallet_path: syn::Path = parse_quote!(pallet);
     let tree_name_path: syn::Path = parse_quote!(tree_name);
+    let fields_struct_path: syn::Path = parse_quote!(fields_struct);
+    let query_struct_path: syn::Path = parse_quote!(query_struct);
 
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
@@ -143,70 +198,191 @@ fn document_derive_inner(
             _ => None,
         });
 
-    let tree_name = l_attrs
-        .clone()
-        .filter_map(|x| match x {
-            syn::Meta::NameValue(mnv) => Some(mnv),
-            _ => None,
-        })
-        .filter(|x| x.path == tree_name_path)
-        .filter_map(|x| match x.lit {
-            syn::Lit::Str(s) => Some(s.value()),
-            _ => None,
-        })
-        .next()
-        .map(|s| quote!(Some(#s.into())))
-        .unwrap_or_else(|| quote!(None));
+    let str_attr = |path: &syn::Path| {
+        l_attrs
+            .clone()
+            .filter_map(|x| match x {
+                syn::Meta::NameValue(mnv) => Some(mnv),
+                _ => None,
+            })
+            .filter(|x| &x.path == path)
+            .filter_map(|x| match x.lit {
+                syn::Lit::Str(s) => Some(s.value()),
+                _ => None,
+            })
+            .next()
+    };
+
+    let tree_name =
+        str_attr(&tree_name_path).map(|s| quote!(Some(#s.into()))).unwrap_or_else(|| quote!(None));
+
+    let field_metas = data.fields.iter().map(handle_field).collect::<Result<Vec<_>, _>>()?;
+
+    let unique_fields = field_metas
+        .iter()
+        .filter(|FieldMeta { is_unique, .. }| *is_unique)
+        .map(|FieldMeta { ident, .. }| ident.clone())
+        .collect::<Vec<_>>();
+
+    let unique_field_names = unique_fields.iter().map(|x| x.to_string()).collect::<Vec<_>>();
 
-    let field_metas = data
-        .fields
+    let sled_index_fields = field_metas
         .iter()
-        .map(handle_field)
-        .filter_map(Result::transpose)
-        .collect::<Result<Vec<_>, _>>()?;
+        .filter(|FieldMeta { is_sled_index, .. }| *is_sled_index)
+        .map(|FieldMeta { ident, .. }| ident.clone())
+        .collect::<Vec<_>>();
+
+    let sled_index_field_names =
+        sled_index_fields.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+
+    let field_metas = field_metas
+        .into_iter()
+        .filter(|FieldMeta { is_indexed, .. }| *is_indexed)
+        .collect::<Vec<_>>();
+
+    let vis = &input.vis;
+
+    let fields_name = match str_attr(&fields_struct_path) {
+        Some(s) => syn::parse_str::<syn::Ident>(&s)?,
+        None => format_ident!("{}Fields", name),
+    };
+    let query_name = match str_attr(&query_struct_path) {
+        Some(s) => syn::parse_str::<syn::Ident>(&s)?,
+        None => format_ident!("{}Query", name),
+    };
+
+    let fields_doc = format!("Index fields of `{}`, generated by `pallet_macros`", name);
+    let query_doc = format!("Typed query helpers for `{}`, generated by `pallet_macros`", name);
+
+    let field_idents = field_metas.iter().map(|FieldMeta { ident, .. }| ident).collect::<Vec<_>>();
 
     let index_fields = field_metas.iter()
-        .map(|FieldMeta { name, ty, opts, .. }| quote!(schema_builder.add_field(<#ty as pallet::search::FieldValue>::field_entry(#name, #opts))))
+        .map(|FieldMeta { ident, name, ty, opts, .. }| quote!(#ident: schema_builder.add_field(<#ty as pallet::search::FieldValue>::field_entry(#name, #opts))))
         .collect::<Vec<_>>();
 
-    let doc_fields = field_metas.iter().enumerate()
-        .map(|(idx, FieldMeta { ident, ty, .. })|
+    let doc_fields = field_metas.iter()
+        .map(|FieldMeta { ident, ty, value, .. }|
             quote! {
-                if let Some(val) = <#ty as pallet::search::FieldValue>::into_value(self.#ident.clone().into()) {
-                    doc.add(pallet::ext::tantivy::schema::FieldValue::new(fields[#idx], val));
+                for val in <#ty as pallet::search::FieldValue>::into_values(#value) {
+                    doc.add(pallet::ext::tantivy::schema::FieldValue::new(index_fields.#ident, val));
                 }
             })
         .collect::<Vec<_>>();
 
+    let query_fields = field_metas.iter()
+        .map(|FieldMeta { ident, name, ty, .. }| {
+            let doc = format!("Query helpers for the `{}` index field", name);
+            quote! {
+                #[doc = #doc]
+                pub fn #ident(&self) -> pallet::search::TypedField<<#ty as pallet::search::FieldValue>::Item> {
+                    pallet::search::TypedField::new(#name)
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
     let default_search_fields = field_metas
         .iter()
-        .enumerate()
-        .filter(|(_, FieldMeta { is_default_search_field, .. })| *is_default_search_field)
-        .map(|(idx, _)| quote!(fields[#idx]))
+        .filter(|FieldMeta { is_default_search_field, .. }| *is_default_search_field)
+        .map(|FieldMeta { ident, .. }| quote!(fields.#ident))
+        .collect::<Vec<_>>();
+
+    let suggest_field_names = field_metas
+        .iter()
+        .filter(|FieldMeta { is_suggest, .. }| *is_suggest)
+        .map(|FieldMeta { name, .. }| name)
         .collect::<Vec<_>>();
 
+    // Generic structs get a generic query struct, as index field types may use the parameters.
+    let (query_struct, query_value) = if input.generics.params.is_empty() {
+        let query_struct = quote! {
+            #[doc = #query_doc]
+            #[derive(Debug, Clone, Copy, Default)]
+            #vis struct #query_name;
+        };
+        (query_struct, quote!(#query_name))
+    } else {
+        let generics = &input.generics;
+        let query_struct = quote! {
+            #[doc = #query_doc]
+            #vis struct #query_name #generics #where_clause {
+                _marker: std::marker::PhantomData<fn() -> #name #ty_generics>,
+            }
+
+            impl #impl_generics std::clone::Clone for #query_name #ty_generics #where_clause {
+                fn clone(&self) -> Self {
+                    *self
+                }
+            }
+
+            impl #impl_generics std::marker::Copy for #query_name #ty_generics #where_clause {}
+
+            impl #impl_generics std::default::Default for #query_name #ty_generics #where_clause {
+                fn default() -> Self {
+                    #query_name { _marker: std::marker::PhantomData }
+                }
+            }
+
+            impl #impl_generics std::fmt::Debug for #query_name #ty_generics #where_clause {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str(stringify!(#query_name))
+                }
+            }
+        };
+        (query_struct, quote!(#query_name { _marker: std::marker::PhantomData }))
+    };
+
     let out = quote! {
+        #[doc = #fields_doc]
+        #[derive(Debug, Clone, Copy)]
+        #vis struct #fields_name {
+            #(pub #field_idents: pallet::ext::tantivy::schema::Field,)*
+        }
+
+        #query_struct
+
+        impl #impl_generics #query_name #ty_generics #where_clause {
+            #(#query_fields)*
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Typed query helpers for each index field, generated by `pallet_macros`.
+            pub fn q() -> #query_name #ty_generics {
+                #query_value
+            }
+        }
+
         impl #impl_generics pallet::DocumentLike for #name #ty_generics #where_clause {
 
-            type IndexFieldsType = pallet::search::FieldsContainer;
+            type IndexFieldsType = #fields_name;
 
             fn tree_builder() -> pallet::db::TreeBuilder {
                 let mut out = pallet::db::TreeBuilder::default();
                 if let std::option::Option::<std::string::String>::Some(tree_name) = #tree_name {
                     out = out.with_tree_name(tree_name);
                 }
+                #(out = out.with_unique_field(#unique_field_names);)*
+                #(out = out.with_sled_index(#sled_index_field_names);)*
                 out
             }
 
+            fn unique_keys(&self) -> std::vec::Vec<(&'static str, std::vec::Vec<std::vec::Vec<u8>>)> {
+                vec![#((#unique_field_names, pallet::db::FieldKeys::field_keys(&self.#unique_fields)),)*]
+            }
+
+            fn sled_index_keys(&self) -> std::vec::Vec<(&'static str, std::vec::Vec<std::vec::Vec<u8>>)> {
+                vec![#((#sled_index_field_names, pallet::db::FieldKeys::field_keys(&self.#sled_index_fields)),)*]
+            }
+
             fn index_builder() -> pallet::search::IndexBuilder<Self::IndexFieldsType> {
                 let out = pallet::search::IndexBuilder::default()
                     .with_fields_builder(|schema_builder| {
-                        Ok(pallet::search::FieldsContainer(vec![#(#index_fields,)*]))
+                        Ok(#fields_name { #(#index_fields,)* })
                     })
                     .with_default_search_fields_builder(|fields| {
-                        let fields = &fields.0;
                         vec![#(#default_search_fields,)*]
-                    });
+                    })
+                    #(.with_suggest_field(#suggest_field_names))*;
                 out
             }
 
@@ -216,8 +392,6 @@ fn document_derive_inner(
             ) -> pallet::err::Result<pallet::ext::tantivy::Document> {
                 use pallet::search::FieldValue;
 
-                let fields = &index_fields.0;
-
                 let mut doc = pallet::ext::tantivy::Document::new();
                 #(#doc_fields)*
 
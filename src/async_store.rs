@@ -0,0 +1,376 @@
+use crate::transaction::Participant;
+use crate::{err, search, Document, DocumentLike, Store};
+use futures_channel::oneshot;
+use std::future::Future;
+use std::sync::{mpsc, Arc};
+
+// Most `Document`s written in one batch by the writer thread.
+const MAX_BATCH: usize = 1024;
+
+// In-place update function queued by `AsyncStore::update_with`.
+type UpdateFn<T> = Box<dyn FnMut(&mut T) + Send>;
+
+// A write queued for the writer thread, with the channel for its result.
+enum Write<T> {
+    Create(Vec<T>, oneshot::Sender<err::Result<Vec<u64>>>),
+    Update(Vec<Document<T>>, oneshot::Sender<err::Result<()>>),
+    UpdateWith(u64, UpdateFn<T>, oneshot::Sender<err::Result<Option<Document<T>>>>),
+    Delete(Vec<u64>, oneshot::Sender<err::Result<()>>),
+}
+
+impl<T> Write<T> {
+    fn len(&self) -> usize {
+        match self {
+            Write::Create(inners, _) => inners.len(),
+            Write::Update(docs, _) => docs.len(),
+            Write::UpdateWith(..) => 1,
+            Write::Delete(ids, _) => ids.len(),
+        }
+    }
+
+    // In-place updates read the `Document` in their own transaction, so are never batched.
+    fn same_kind(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+            && !matches!(self, Write::UpdateWith(..))
+    }
+
+    fn fail(self, e: err::Error) {
+        match self {
+            Write::Create(_, sender) => {
+                let _ = sender.send(Err(e));
+            }
+            Write::Update(_, sender) | Write::Delete(_, sender) => {
+                let _ = sender.send(Err(e));
+            }
+            Write::UpdateWith(_, _, sender) => {
+                let _ = sender.send(Err(e));
+            }
+        }
+    }
+}
+
+/**
+Wrapper around `Store` for use from async code, available with the `async` feature
+
+Blocking work (finds, searches and commits) is run on a `rayon` thread pool owned by the
+`AsyncStore`, rather than `rayon`'s global pool, so it cannot starve other parallel work (such as
+loading search hits), any executor (or `futures::executor` for tests) can be used, and the number
+of threads is bounded. Writes, including `update_with`, are queued to a single writer thread,
+which batches consecutive writes of the same kind into one transaction and index commit. If a
+batch's transaction fails, its writes are retried one at a time, so each write gets its own
+result. If the transaction commits but updating the search index fails, every write in the batch
+gets the error as an `err::Error::Shared` (the index is brought back in sync by a later write,
+commit, or `StoreBuilder::finish`).
+
+Writes are queued when the method is called, not when the future is first polled. Dropping the
+future does not cancel a queued write, it only discards its result.
+
+## Usage:
+
+```rust
+use pallet::{err, AsyncStore, DocumentLike};
+
+async fn create_and_find<T>(store: &AsyncStore<T>, inner: T) -> err::Result<Option<T>>
+where
+    T: DocumentLike + Send + Sync + 'static,
+    T::IndexFieldsType: Send + Sync,
+{
+    let id = store.create(inner).await?;
+    let doc = store.find(id).await?;
+    Ok(doc.map(|doc| doc.inner))
+}
+
+// Any executor can be used, e.g. a local one in tests.
+fn create_and_find_blocking<T>(store: &AsyncStore<T>, inner: T) -> err::Result<Option<T>>
+where
+    T: DocumentLike + Send + Sync + 'static,
+    T::IndexFieldsType: Send + Sync,
+{
+    futures_executor::block_on(create_and_find(store, inner))
+}
+```
+*/
+pub struct AsyncStore<T: DocumentLike> {
+    store: Arc<Store<T>>,
+    writes: mpsc::Sender<Write<T>>,
+    pool: rayon::ThreadPool,
+}
+
+impl<T> AsyncStore<T>
+where
+    T: DocumentLike + Send + Sync + 'static,
+    T::IndexFieldsType: Send + Sync,
+{
+    /// Wrap a `Store`, starting its writer thread and a thread pool with `rayon`'s default
+    /// number of threads.
+    ///
+    /// The writer thread finishes any queued writes and stops once the `AsyncStore` is dropped.
+    ///
+    /// Panics if the thread pool cannot be created, see `with_thread_pool` to handle this.
+    pub fn new(store: Store<T>) -> Self {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .thread_name(|idx| format!("pallet-async-{}", idx))
+            .build()
+            .expect("failed to create the `AsyncStore` thread pool");
+
+        AsyncStore::with_thread_pool(store, pool)
+    }
+
+    /// Wrap a `Store`, running blocking work on `pool` and starting its writer thread.
+    pub fn with_thread_pool(store: Store<T>, pool: rayon::ThreadPool) -> Self {
+        let store = Arc::new(store);
+        let (writes, receiver) = mpsc::channel();
+
+        let writer_store = store.clone();
+        std::thread::spawn(move || run_writer(&writer_store, &receiver));
+
+        AsyncStore { store, writes, pool }
+    }
+
+    /// Get the wrapped `Store`. Its methods block, so should not be called on an async executor.
+    pub fn store(&self) -> &Arc<Store<T>> {
+        &self.store
+    }
+
+    /// Create a new `Document`, returns the persisted document's `id`.
+    pub fn create(&self, inner: T) -> impl Future<Output = err::Result<u64>> {
+        let ids = self.create_multi(vec![inner]);
+        async move { Ok(ids.await?[0]) }
+    }
+
+    /// Create new `Document`s, returns the persisted documents' `id`s.
+    pub fn create_multi(&self, inners: Vec<T>) -> impl Future<Output = err::Result<Vec<u64>>> {
+        self.queue(|sender| Write::Create(inners, sender))
+    }
+
+    /// Update a given `Document`, see `Store::update`.
+    pub fn update(&self, doc: Document<T>) -> impl Future<Output = err::Result<()>> {
+        self.update_multi(vec![doc])
+    }
+
+    /// Update given `Document`s, see `Store::update_multi`.
+    pub fn update_multi(&self, docs: Vec<Document<T>>) -> impl Future<Output = err::Result<()>> {
+        self.queue(|sender| Write::Update(docs, sender))
+    }
+
+    /// Update a `Document` in place, see `Store::update_with`.
+    ///
+    /// Queued to the writer thread like other writes, but always run in its own transaction.
+    pub fn update_with<F>(
+        &self,
+        id: u64,
+        f: F,
+    ) -> impl Future<Output = err::Result<Option<Document<T>>>>
+    where
+        F: FnMut(&mut T) + Send + 'static,
+    {
+        self.queue(move |sender| Write::UpdateWith(id, Box::new(f), sender))
+    }
+
+    /// Delete a `Document` by `id`.
+    pub fn delete(&self, id: u64) -> impl Future<Output = err::Result<()>> {
+        self.delete_multi(vec![id])
+    }
+
+    /// Delete `Document`s by `id`s.
+    pub fn delete_multi(&self, ids: Vec<u64>) -> impl Future<Output = err::Result<()>> {
+        self.queue(|sender| Write::Delete(ids, sender))
+    }
+
+    /// Find a single `Document` by its `id`. Does not use the search index.
+    pub fn find(&self, id: u64) -> impl Future<Output = err::Result<Option<Document<T>>>> {
+        let store = self.store.clone();
+        spawn_blocking(&self.pool, move || store.find(id))
+    }
+
+    /// Get all `Documents` from the datastore. Does not use the search index.
+    pub fn all(&self) -> impl Future<Output = err::Result<Vec<Document<T>>>> {
+        let store = self.store.clone();
+        spawn_blocking(&self.pool, move || store.all())
+    }
+
+    /// Search the datastore, see `Store::search`.
+    pub fn search<I>(&self, searcher: I) -> impl Future<Output = Result<I::Item, I::Error>>
+    where
+        I: search::Searcher<T> + Send + 'static,
+        I::Item: Send + 'static,
+        I::Error: Send + 'static,
+    {
+        let store = self.store.clone();
+        spawn_blocking(&self.pool, move || store.search(searcher))
+    }
+
+    /// Complete the last term of `prefix` from a suggest field, see `Store::suggest`.
+    pub fn suggest(
+        &self,
+        prefix: &str,
+        field_name: &str,
+        limit: usize,
+    ) -> impl Future<Output = err::Result<Vec<search::Suggestion>>> {
+        let store = self.store.clone();
+        let (prefix, field_name) = (prefix.to_string(), field_name.to_string());
+        spawn_blocking(&self.pool, move || store.suggest(&prefix, &field_name, limit))
+    }
+
+    /// Commit any outstanding changes to the search index, see `Store::commit`.
+    pub fn commit(&self) -> impl Future<Output = err::Result<()>> {
+        let store = self.store.clone();
+        spawn_blocking(&self.pool, move || store.commit())
+    }
+
+    fn queue<R, F>(&self, write: F) -> impl Future<Output = err::Result<R>>
+    where
+        F: FnOnce(oneshot::Sender<err::Result<R>>) -> Write<T>,
+    {
+        let (sender, receiver) = oneshot::channel();
+        // If the writer thread has stopped, the `sender` is dropped and the receiver fails.
+        let _ = self.writes.send(write(sender));
+        receive(receiver)
+    }
+}
+
+impl<T> From<Store<T>> for AsyncStore<T>
+where
+    T: DocumentLike + Send + Sync + 'static,
+    T::IndexFieldsType: Send + Sync,
+{
+    fn from(store: Store<T>) -> Self {
+        AsyncStore::new(store)
+    }
+}
+
+// Run `f` on `pool`, returns a future of its result.
+fn spawn_blocking<F, R, E>(pool: &rayon::ThreadPool, f: F) -> impl Future<Output = Result<R, E>>
+where
+    F: FnOnce() -> Result<R, E> + Send + 'static,
+    R: Send + 'static,
+    E: From<err::Error> + Send + 'static,
+{
+    let (sender, receiver) = oneshot::channel();
+
+    pool.spawn(move || {
+        let _ = sender.send(f());
+    });
+
+    receive(receiver)
+}
+
+// Wait for a result sent from a background thread, failing if the thread stopped without sending.
+async fn receive<R, E>(receiver: oneshot::Receiver<Result<R, E>>) -> Result<R, E>
+where
+    E: From<err::Error>,
+{
+    receiver.await.map_err(|_| err::custom("Background thread stopped before completing"))?
+}
+
+// Apply queued writes until every `AsyncStore` sender is dropped.
+fn run_writer<T: DocumentLike>(store: &Store<T>, receiver: &mpsc::Receiver<Write<T>>) {
+    let mut next = receiver.recv().ok();
+
+    while let Some(first) = next.take() {
+        let mut size = first.len();
+        let mut batch = vec![first];
+
+        while size < MAX_BATCH {
+            match receiver.try_recv() {
+                Ok(write) if write.same_kind(&batch[0]) => {
+                    size += write.len();
+                    batch.push(write);
+                }
+                Ok(write) => {
+                    next = Some(write);
+                    break;
+                }
+                Err(_) => break,
+            }
+        }
+
+        apply_batch(store, batch);
+
+        if next.is_none() {
+            next = receiver.recv().ok();
+        }
+    }
+}
+
+// Apply writes of the same kind in one transaction, falling back to one transaction per write if
+// it aborts.
+fn apply_batch<T: DocumentLike>(store: &Store<T>, batch: Vec<Write<T>>) {
+    if batch.len() == 1 {
+        return batch.into_iter().for_each(|write| apply_one(store, write));
+    }
+
+    let written = match &batch[0] {
+        Write::Create(..) => {
+            let creates = batch.iter().flat_map(|write| match write {
+                Write::Create(inners, _) => inners.as_slice(),
+                _ => &[],
+            });
+            let creates = creates.map(|inner| (None, inner)).collect::<Vec<_>>();
+            store.tree_creates(&creates).map(|(ids, changes)| (Some(ids), changes))
+        }
+        Write::Update(..) => {
+            let updates = batch.iter().flat_map(|write| match write {
+                Write::Update(docs, _) => docs.as_slice(),
+                _ => &[],
+            });
+            let updates =
+                updates.map(|doc| (doc.id, doc.version(), &doc.inner)).collect::<Vec<_>>();
+            store.tree_updates(&updates).map(|(_, changes)| (None, changes))
+        }
+        Write::Delete(..) => {
+            let ids = batch.iter().flat_map(|write| match write {
+                Write::Delete(ids, _) => ids.as_slice(),
+                _ => &[],
+            });
+            store.tree_deletes(&ids.copied().collect::<Vec<_>>()).map(|changes| (None, changes))
+        }
+        // Never batched, see `Write::same_kind`.
+        Write::UpdateWith(..) => {
+            return batch.into_iter().for_each(|write| apply_one(store, write));
+        }
+    };
+
+    // Nothing was written, so each write can be retried on its own.
+    let (ids, changes) = match written {
+        Ok(written) => written,
+        Err(_) => return batch.into_iter().for_each(|write| apply_one(store, write)),
+    };
+
+    // The whole batch was written, so must not be retried.
+    if let Err(e) = store.apply_changes(changes) {
+        let e = Arc::new(e);
+        return batch.into_iter().for_each(|write| write.fail(err::Error::Shared(e.clone())));
+    }
+
+    let mut ids = ids.unwrap_or_default().into_iter();
+
+    for write in batch {
+        match write {
+            Write::Create(inners, sender) => {
+                let _ = sender.send(Ok(ids.by_ref().take(inners.len()).collect()));
+            }
+            Write::Update(_, sender) | Write::Delete(_, sender) => {
+                let _ = sender.send(Ok(()));
+            }
+            Write::UpdateWith(..) => {}
+        }
+    }
+}
+
+fn apply_one<T: DocumentLike>(store: &Store<T>, write: Write<T>) {
+    match write {
+        Write::Create(inners, sender) => {
+            let _ = sender.send(store.create_multi(&inners));
+        }
+        Write::Update(docs, sender) => {
+            let _ = sender.send(store.update_multi(&docs));
+        }
+        Write::UpdateWith(id, f, sender) => {
+            let _ = sender.send(store.update_with(id, f));
+        }
+        Write::Delete(ids, sender) => {
+            let _ = sender.send(store.delete_multi(&ids));
+        }
+    }
+}
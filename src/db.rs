@@ -1,9 +1,74 @@
 use crate::err;
+use sled::transaction::{
+    ConflictableTransactionError, ConflictableTransactionResult, TransactionalTree,
+};
+use sled::Transactional;
+use std::convert::TryInto;
 use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+mod index_key;
+mod iter;
+mod migration;
+mod watch;
+
+pub use index_key::IndexKey;
+pub use iter::Iter;
+pub use migration::Migrations;
+pub use watch::{Event, Watch, WatchReceiver};
+
+pub(crate) use watch::ChangeRecord;
+
+// For use primarily by `pallet_macros`.
+#[doc(hidden)]
+pub use index_key::FieldKeys;
+
+const OPSTAMP_KEY: &[u8] = b"opstamp";
+const KEY_FORMAT_KEY: &[u8] = b"key_format";
+const MIGRATING_KEY: &[u8] = b"migrating";
+const NEXT_ID_KEY: &[u8] = b"next_id";
+const SCHEMA_VERSION_KEY: &[u8] = b"schema_version";
+const MIGRATION_PROGRESS_KEY: &[u8] = b"migration_progress";
+const REINDEX_KEY: &[u8] = b"reindex";
+
+// Most `Document`s converted in a single migration transaction.
+const MIGRATION_BATCH: usize = 1000;
+
+// Version of the tree key format, recorded under `KEY_FORMAT_KEY`. Trees without one were written
+// by earlier versions with little-endian keys (version `0`); since version `1` keys are stored
+// big-endian, so that the tree iterates in `id` order.
+const KEY_FORMAT_VERSION: u64 = 1;
+
+/// Encode an `id` as a tree key.
+///
+/// Every stored `id` (and other `u64`, such as log sequence numbers) uses this big-endian encoding.
+pub(crate) fn id_key(id: u64) -> [u8; 8] {
+    id.to_be_bytes()
+}
+
+/// Decode an `id` encoded with `id_key`.
+pub(crate) fn key_id(key: &[u8]) -> err::Result<u64> {
+    Ok(u64::from_be_bytes(key.try_into().map_err(err::custom)?))
+}
 
 /// Wrapper for `sled::Tree` and its `sled::Db` (included for `id` generation)
+///
+/// Also holds a tree of `Document` versions, the trees used to keep the search index in sync: a
+/// log of pending index updates, and store metadata such as the last applied `tantivy` opstamp; a
+/// tree per unique field, mapping encoded field values to `id`s; and a tree per sled index field,
+/// holding encoded field values followed by `id`s. While there are `Watch`ers, changes are also
+/// recorded in a change feed tree, and removed again once the write has committed.
 pub struct Tree {
     inner: sled::Tree,
+    id_strategy: IdStrategy,
+    pub(crate) pending: sled::Tree,
+    pub(crate) versions: sled::Tree,
+    pub(crate) meta: sled::Tree,
+    pub(crate) changes: sled::Tree,
+    pub(crate) watchers: Arc<AtomicUsize>,
+    pub(crate) unique: Vec<(String, sled::Tree)>,
+    pub(crate) indexes: Vec<(String, sled::Tree)>,
 }
 
 impl Deref for Tree {
@@ -19,6 +84,579 @@ impl Tree {
     pub fn builder() -> TreeBuilder {
         TreeBuilder::default()
     }
+
+    /// All trees that take part in write transactions, in the order expected by `transaction_view`.
+    pub(crate) fn transaction_trees(&self) -> Vec<&sled::Tree> {
+        // Trees are written in this order on commit, `get_versioned` relies on the main tree
+        // being written before the versions tree.
+        let mut trees = vec![&self.inner, &self.pending, &self.versions, &self.meta, &self.changes];
+        trees.extend(self.unique.iter().chain(&self.indexes).map(|(_, tree)| tree));
+        trees
+    }
+
+    /// Split the transactional views of `transaction_trees` into a `Transaction`.
+    pub(crate) fn transaction_view<'a>(&self, view: &'a [TransactionalTree]) -> Transaction<'a> {
+        Transaction {
+            tree: &view[0],
+            pending: &view[1],
+            versions: &view[2],
+            meta: &view[3],
+            changes: &view[4],
+            record_changes: self.watchers.load(Ordering::SeqCst) > 0,
+            secondary: &view[5..5 + self.unique.len() + self.indexes.len()],
+        }
+    }
+
+    /// Run a transaction over the main tree and all of its supporting trees.
+    pub(crate) fn transaction<F, R>(&self, f: F) -> err::Result<R>
+    where
+        F: Fn(&Transaction<'_>) -> ConflictableTransactionResult<R, err::Error>,
+    {
+        Ok(self
+            .transaction_trees()
+            .as_slice()
+            .transaction(|view| f(&self.transaction_view(view)))?)
+    }
+
+    /// Generate an `id` for a new `Document`, according to the `IdStrategy`.
+    ///
+    /// `id`s already in use (e.g. caller-supplied ones) are skipped, except with
+    /// `IdStrategy::Custom`.
+    pub(crate) fn generate_id(
+        &self,
+        txn: &Transaction<'_>,
+    ) -> ConflictableTransactionResult<u64, err::Error> {
+        loop {
+            let id = match &self.id_strategy {
+                IdStrategy::Sled => txn.tree.generate_id()?,
+                IdStrategy::Monotonic => {
+                    let id =
+                        txn.meta.get(NEXT_ID_KEY)?.map(|v| key_id(&v)).transpose()?.unwrap_or(0);
+                    txn.meta.insert(NEXT_ID_KEY, &id_key(id.saturating_add(1)))?;
+                    id
+                }
+                IdStrategy::Custom(f) => return Ok(f()),
+            };
+
+            if txn.tree.get(id_key(id))?.is_none() {
+                return Ok(id);
+            }
+        }
+    }
+
+    /// Record that a caller-supplied `id` is in use, so the `IdStrategy` does not generate it.
+    pub(crate) fn reserve_id(
+        &self,
+        txn: &Transaction<'_>,
+        id: u64,
+    ) -> ConflictableTransactionResult<(), err::Error> {
+        if let IdStrategy::Monotonic = self.id_strategy {
+            let next = txn.meta.get(NEXT_ID_KEY)?.map(|v| key_id(&v)).transpose()?.unwrap_or(0);
+            if id >= next {
+                txn.meta.insert(NEXT_ID_KEY, &id_key(id.saturating_add(1)))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the serialized value and version for `id`.
+    ///
+    /// The version is read first, so a concurrent write can only pair an older version with a
+    /// newer value (failing a later version check), never the reverse.
+    pub(crate) fn get_versioned(&self, id: u64) -> err::Result<Option<(sled::IVec, u64)>> {
+        let version = self.versions.get(id_key(id))?;
+
+        match self.inner.get(id_key(id))? {
+            Some(value) => Ok(Some((value, version.map(|v| key_id(&v)).transpose()?.unwrap_or(0)))),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns `true` if there are any unique field or sled index trees.
+    pub(crate) fn has_secondary(&self) -> bool {
+        !self.unique.is_empty() || !self.indexes.is_empty()
+    }
+
+    /// Get the tree for a unique field by name.
+    pub(crate) fn unique_tree(&self, field_name: &str) -> err::Result<&sled::Tree> {
+        self.unique
+            .iter()
+            .find(|(name, _)| name == field_name)
+            .map(|(_, tree)| tree)
+            .ok_or_else(|| err::custom(format!("Unknown unique field `{}`", field_name)))
+    }
+
+    /// Get the sled index tree for a field by name.
+    pub(crate) fn index_tree(&self, field_name: &str) -> err::Result<&sled::Tree> {
+        self.indexes
+            .iter()
+            .find(|(name, _)| name == field_name)
+            .map(|(_, tree)| tree)
+            .ok_or_else(|| err::custom(format!("Unknown sled index field `{}`", field_name)))
+    }
+
+    /// Replace the secondary entries for `id` from `old_keys` to `new_keys`.
+    ///
+    /// Fails with `err::Error::UniqueConflict` if a new unique key belongs to a
+    /// different `id`.
+    pub(crate) fn update_secondary(
+        &self,
+        txn: &Transaction<'_>,
+        id: u64,
+        old_keys: &SecondaryKeys,
+        new_keys: &SecondaryKeys,
+    ) -> ConflictableTransactionResult<(), err::Error> {
+        let (unique_trees, index_trees) = txn.secondary.split_at(self.unique.len());
+
+        let id_bytes = id_key(id);
+
+        for ((field_name, _), unique_tree) in self.unique.iter().zip(unique_trees) {
+            let new_keys = keys_for(&new_keys.unique, field_name);
+
+            for key in keys_for(&old_keys.unique, field_name) {
+                if !new_keys.contains(&key)
+                    && unique_tree.get(&key)?.map(|v| v == id_bytes).unwrap_or(false)
+                {
+                    unique_tree.remove(key)?;
+                }
+            }
+
+            for key in new_keys {
+                if let Some(existing) = unique_tree.get(&key)? {
+                    if existing != id_bytes {
+                        let existing_id = key_id(&existing)?;
+                        return Err(ConflictableTransactionError::Abort(
+                            err::Error::UniqueConflict {
+                                field: field_name.clone(),
+                                id: existing_id,
+                            },
+                        ));
+                    }
+                }
+                unique_tree.insert(key, &id_bytes)?;
+            }
+        }
+
+        for ((field_name, _), index_tree) in self.indexes.iter().zip(index_trees) {
+            let new_keys = keys_for(&new_keys.indexed, field_name);
+
+            for key in keys_for(&old_keys.indexed, field_name) {
+                if !new_keys.contains(&key) {
+                    index_tree.remove(index_entry(&key, id))?;
+                }
+            }
+
+            for key in new_keys {
+                index_tree.insert(index_entry(&key, id), &[])?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Meta keys recording that each secondary tree has been built.
+    fn built_keys(&self) -> Vec<String> {
+        self.unique
+            .iter()
+            .map(|x| ("unique", x))
+            .chain(self.indexes.iter().map(|x| ("index", x)))
+            .map(|(prefix, (field_name, _))| format!("built/{}/{}", prefix, field_name))
+            .collect()
+    }
+
+    /// Returns `true` if the secondary trees have been built from the existing `Document`s.
+    pub(crate) fn secondary_built(&self) -> err::Result<bool> {
+        for key in self.built_keys() {
+            if !self.meta.contains_key(key)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Clear the secondary trees, ahead of building them from the existing `Document`s.
+    pub(crate) fn clear_secondary(&self) -> err::Result<()> {
+        for (_, tree) in self.unique.iter().chain(&self.indexes) {
+            tree.clear()?;
+        }
+        Ok(())
+    }
+
+    /// Record that the secondary trees have been built from the existing `Document`s.
+    pub(crate) fn mark_secondary_built(&self) -> err::Result<()> {
+        for key in self.built_keys() {
+            self.meta.insert(key, &[])?;
+        }
+        Ok(())
+    }
+
+    /// Get all logged `(id, seq)` entries that have not yet been applied to the index.
+    pub(crate) fn pending_entries(&self) -> err::Result<Vec<(u64, u64)>> {
+        self.pending
+            .iter()
+            .map(|res| {
+                let (k, v) = res?;
+                Ok((key_id(&k)?, key_id(&v)?))
+            })
+            .collect()
+    }
+
+    /// Mark logged entries as applied and record the `tantivy` opstamp of the commit that applied them.
+    ///
+    /// Entries that have been logged again since are left in place.
+    pub(crate) fn mark_applied(&self, entries: &[(u64, u64)], opstamp: u64) -> err::Result<()> {
+        self.meta.insert(OPSTAMP_KEY, &id_key(opstamp))?;
+        for (id, seq) in entries {
+            let _ = self.pending.compare_and_swap(
+                id_key(*id),
+                Some(&id_key(*seq)),
+                None as Option<&[u8]>,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Convert keys written by earlier versions (little-endian `id`s) to big-endian, if the
+    /// recorded key format version is older than `KEY_FORMAT_VERSION`.
+    ///
+    /// Keys are first copied to a staging tree, then copied back once the staging tree is
+    /// complete, so the migration can be restarted if interrupted.
+    fn migrate_keys(&self, staging: &sled::Tree) -> err::Result<()> {
+        let version = self.meta.get(KEY_FORMAT_KEY)?.map(|v| key_id(&v)).transpose()?;
+
+        match version {
+            Some(version) if version == KEY_FORMAT_VERSION => return Ok(()),
+            Some(version) if version > KEY_FORMAT_VERSION => {
+                return Err(err::custom(format!(
+                    "Tree has key format version `{}`, written by a later version",
+                    version
+                )));
+            }
+            _ => {}
+        }
+
+        let migrating = self.meta.contains_key(MIGRATING_KEY)?;
+
+        if self.inner.is_empty() && !migrating {
+            self.meta.insert(KEY_FORMAT_KEY, &id_key(KEY_FORMAT_VERSION))?;
+            return Ok(());
+        }
+
+        if !migrating {
+            staging.clear()?;
+            for res in self.inner.iter() {
+                let (k, v) = res?;
+                let id = u64::from_le_bytes(k.as_ref().try_into().map_err(err::custom)?);
+                staging.insert(id_key(id), v)?;
+            }
+            staging.flush()?;
+            self.meta.insert(MIGRATING_KEY, &[])?;
+        }
+
+        self.inner.clear()?;
+        for res in staging.iter() {
+            let (k, v) = res?;
+            self.inner.insert(k, v)?;
+        }
+        self.inner.flush()?;
+
+        self.meta.insert(KEY_FORMAT_KEY, &id_key(KEY_FORMAT_VERSION))?;
+        self.meta.remove(MIGRATING_KEY)?;
+        staging.clear()?;
+
+        Ok(())
+    }
+
+    /// Run any outstanding `Migrations` on the stored `Document`s.
+    ///
+    /// `Document`s are converted in batches, recording progress in the same transaction, so the
+    /// migration can be restarted if interrupted. Completing a migration requests a reindex and
+    /// marks the secondary trees as unbuilt, so both are rebuilt by the `Store`.
+    fn migrate_documents(&self, migrations: &Migrations) -> err::Result<()> {
+        let latest = migrations.version();
+
+        let mut version = match self.meta.get(SCHEMA_VERSION_KEY)? {
+            Some(v) => key_id(&v)?,
+            None if self.inner.is_empty() => latest,
+            None => 0,
+        };
+
+        if version > latest {
+            return Err(err::custom(format!(
+                "Stored schema version `{}` is newer than the latest version `{}`",
+                version, latest
+            )));
+        }
+
+        while version < latest {
+            let step = migrations.step(version)?;
+
+            let mut start = match self.meta.get(MIGRATION_PROGRESS_KEY)? {
+                Some(progress) if key_id(&progress[..8])? == version => {
+                    std::ops::Bound::Excluded(progress[8..].to_vec())
+                }
+                _ => std::ops::Bound::Unbounded,
+            };
+
+            loop {
+                let batch = self
+                    .inner
+                    .range((start.clone(), std::ops::Bound::Unbounded))
+                    .take(MIGRATION_BATCH)
+                    .map(|res| {
+                        let (k, v) = res?;
+                        Ok((k, step(&v)?))
+                    })
+                    .collect::<err::Result<Vec<_>>>()?;
+
+                let last = match batch.last() {
+                    Some((k, _)) => k.clone(),
+                    None => break,
+                };
+
+                let progress = [&id_key(version)[..], &last].concat();
+
+                (&self.inner, &self.meta).transaction(|(inner, meta)| {
+                    for (k, v) in &batch {
+                        inner.insert(k, v.as_slice())?;
+                    }
+                    meta.insert(MIGRATION_PROGRESS_KEY, progress.as_slice())?;
+                    Ok::<_, ConflictableTransactionError<err::Error>>(())
+                })?;
+
+                start = std::ops::Bound::Excluded(last.to_vec());
+            }
+
+            version += 1;
+
+            let built_keys = self.built_keys();
+
+            self.meta.transaction(|meta| {
+                meta.insert(SCHEMA_VERSION_KEY, &id_key(version))?;
+                meta.remove(MIGRATION_PROGRESS_KEY)?;
+                meta.insert(REINDEX_KEY, &[])?;
+                for key in &built_keys {
+                    meta.remove(key.as_bytes())?;
+                }
+                Ok::<_, ConflictableTransactionError<err::Error>>(())
+            })?;
+        }
+
+        self.meta.insert(SCHEMA_VERSION_KEY, &id_key(version))?;
+
+        Ok(())
+    }
+
+    /// Returns `true` if the search index must be rebuilt, e.g. after a migration.
+    pub(crate) fn reindex_requested(&self) -> err::Result<bool> {
+        Ok(self.meta.contains_key(REINDEX_KEY)?)
+    }
+
+    /// Record that the search index must be rebuilt, until `mark_reindexed` is called.
+    pub(crate) fn request_reindex(&self) -> err::Result<()> {
+        self.meta.insert(REINDEX_KEY, &[])?;
+        Ok(())
+    }
+
+    /// Record that the search index has been rebuilt.
+    pub(crate) fn mark_reindexed(&self) -> err::Result<()> {
+        self.meta.remove(REINDEX_KEY)?;
+        Ok(())
+    }
+
+    /// Remove recorded changes once they have been delivered to `Watch`ers.
+    ///
+    /// Changes are delivered as soon as their transaction commits, so every record up to the
+    /// latest of `entries` is removed, including any left behind by writes that failed to apply
+    /// their index changes.
+    pub(crate) fn clear_changes(&self, entries: &[(u64, u64)]) -> err::Result<()> {
+        let last = match entries.iter().map(|(_, seq)| *seq).max() {
+            Some(last) if !self.changes.is_empty() => last,
+            _ => return Ok(()),
+        };
+
+        for key in self.changes.range(..=id_key(last)).keys() {
+            self.changes.remove(key?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Make sure the stored next `id` is after the highest existing `id`, e.g. when switching to
+    /// `IdStrategy::Monotonic`.
+    fn init_next_id(&self) -> err::Result<()> {
+        let next = match self.inner.last()? {
+            Some((k, _)) => key_id(&k)?.saturating_add(1),
+            None => 0,
+        };
+
+        let stored = self.meta.get(NEXT_ID_KEY)?.map(|v| key_id(&v)).transpose()?.unwrap_or(0);
+
+        if next > stored {
+            self.meta.insert(NEXT_ID_KEY, &id_key(next))?;
+        }
+
+        Ok(())
+    }
+
+    /// The `tantivy` opstamp of the last commit applied to the index, if any.
+    pub(crate) fn opstamp(&self) -> err::Result<Option<u64>> {
+        self.meta.get(OPSTAMP_KEY)?.map(|v| key_id(&v)).transpose()
+    }
+}
+
+/// Transactional views of a `Tree` and its supporting trees, see `Tree::transaction`
+pub(crate) struct Transaction<'a> {
+    pub(crate) tree: &'a TransactionalTree,
+    pub(crate) pending: &'a TransactionalTree,
+    pub(crate) versions: &'a TransactionalTree,
+    pub(crate) meta: &'a TransactionalTree,
+    pub(crate) changes: &'a TransactionalTree,
+    pub(crate) record_changes: bool,
+    pub(crate) secondary: &'a [TransactionalTree],
+}
+
+impl Transaction<'_> {
+    /// Record that the index entry for `id` needs updating, returns the `(id, seq)` log entry.
+    pub(crate) fn log_pending(
+        &self,
+        id: u64,
+    ) -> ConflictableTransactionResult<(u64, u64), err::Error> {
+        let seq = self.pending.generate_id()?;
+        self.pending.insert(&id_key(id), &id_key(seq))?;
+        Ok((id, seq))
+    }
+
+    /// Record a change for any `Watch`ers, keyed by the `seq` of its pending log entry.
+    ///
+    /// `old` and `new` are the serialized values before and after, `version` is the new version.
+    pub(crate) fn record_change(
+        &self,
+        (id, seq): (u64, u64),
+        version: Option<u64>,
+        old: Option<&[u8]>,
+        new: Option<&[u8]>,
+    ) -> ConflictableTransactionResult<(), err::Error> {
+        if !self.record_changes || (old.is_none() && new.is_none()) {
+            return Ok(());
+        }
+
+        let record = ChangeRecord {
+            id,
+            version,
+            old: old.map(|x| x.to_vec()),
+            new: new.map(|x| x.to_vec()),
+        };
+
+        self.changes.insert(&id_key(seq), crate::serialize::serialize(&record)?)?;
+
+        Ok(())
+    }
+
+    /// Check the current version for `id` against `expected`, then store and return the next
+    /// version.
+    ///
+    /// `exists` is whether the `Document` existed before this write; new `Document`s start at
+    /// version `0`, as do those written before versions were stored. Deleted `Document`s leave
+    /// their last version behind as a tombstone, so an `id` that is written again continues from
+    /// it, and a stale version can never match again.
+    pub(crate) fn next_version(
+        &self,
+        id: u64,
+        exists: bool,
+        expected: Option<u64>,
+    ) -> ConflictableTransactionResult<u64, err::Error> {
+        let stored = self.versions.get(id_key(id))?.map(|v| key_id(&v)).transpose()?;
+
+        let current = if exists { Some(stored.unwrap_or(0)) } else { None };
+
+        if let Some(expected) = expected {
+            if current != Some(expected) {
+                return Err(ConflictableTransactionError::Abort(err::Error::VersionConflict {
+                    id,
+                    expected,
+                    actual: current,
+                }));
+            }
+        }
+
+        let next = match (current, stored) {
+            (Some(version), _) | (None, Some(version)) => version + 1,
+            (None, None) => 0,
+        };
+
+        self.versions.insert(&id_key(id), &id_key(next))?;
+
+        Ok(next)
+    }
+}
+
+/// Encoded secondary keys for a `Document`, as returned by `DocumentLike::unique_keys` and
+/// `DocumentLike::sled_index_keys`.
+#[derive(Default)]
+pub(crate) struct SecondaryKeys {
+    pub(crate) unique: Vec<(&'static str, Vec<Vec<u8>>)>,
+    pub(crate) indexed: Vec<(&'static str, Vec<Vec<u8>>)>,
+}
+
+fn keys_for(keys: &[(&str, Vec<Vec<u8>>)], field_name: &str) -> Vec<Vec<u8>> {
+    keys.iter().filter(|(name, _)| *name == field_name).flat_map(|(_, keys)| keys.clone()).collect()
+}
+
+// Sled index entries are the encoded key followed by the big-endian `id`, so entries for equal
+// keys are ordered by `id`.
+fn index_entry(key: &[u8], id: u64) -> Vec<u8> {
+    let mut out = Vec::with_capacity(key.len() + 8);
+    out.extend_from_slice(key);
+    out.extend_from_slice(&id_key(id));
+    out
+}
+
+/// Get the `id` from a sled index entry.
+pub(crate) fn index_entry_id(entry: &[u8]) -> err::Result<u64> {
+    let id_bytes = entry
+        .len()
+        .checked_sub(8)
+        .map(|idx| &entry[idx..])
+        .ok_or_else(|| err::custom("Invalid sled index entry"))?;
+    key_id(id_bytes)
+}
+
+/// How `id`s are generated for new `Document`s
+///
+/// Caller-supplied `id`s (see `Store::insert_with_id` and `Store::upsert`) are never overwritten
+/// by generated ones: `IdStrategy::Sled` and `IdStrategy::Monotonic` skip `id`s that are already
+/// in use, and creating a `Document` with any other `id` that is in use fails with
+/// `err::Error::AlreadyExists`.
+#[derive(Clone, Default)]
+pub enum IdStrategy {
+    /// Use `sled::Db::generate_id` (the default). `id`s are unique across the whole `sled::Db`, but
+    /// may skip values after a restart.
+    #[default]
+    Sled,
+    /// Use a counter stored with the tree, starting after the highest existing `id` and kept
+    /// ahead of any caller-supplied `id`s.
+    Monotonic,
+    /// Use a closure, which may be called more than once per `Document` if a transaction is
+    /// retried. Creating a `Document` fails if the closure returns an `id` that is in use.
+    Custom(std::sync::Arc<dyn Fn() -> u64 + Send + Sync>),
+}
+
+impl IdStrategy {
+    /// Shortcut to create an `IdStrategy::Custom`.
+    pub fn custom<F: Fn() -> u64 + Send + Sync + 'static>(f: F) -> Self {
+        IdStrategy::Custom(std::sync::Arc::new(f))
+    }
+}
+
+impl std::fmt::Debug for IdStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdStrategy::Sled => f.write_str("Sled"),
+            IdStrategy::Monotonic => f.write_str("Monotonic"),
+            IdStrategy::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
 }
 
 /// Builder for `Tree`
@@ -26,14 +664,42 @@ impl Tree {
 pub struct TreeBuilder {
     tree_name: Option<String>,
     db: Option<sled::Db>,
+    unique_fields: Vec<String>,
+    index_fields: Vec<String>,
+    id_strategy: Option<IdStrategy>,
+    migrations: Migrations,
 }
 
 impl TreeBuilder {
     pub(crate) fn merge(self, other: Self) -> Self {
-        let TreeBuilder { tree_name: a1, db: a2 } = self;
-        let TreeBuilder { tree_name: b1, db: b2 } = other;
+        let TreeBuilder {
+            tree_name: a1,
+            db: a2,
+            unique_fields: mut a3,
+            index_fields: mut a4,
+            id_strategy: a5,
+            migrations: a6,
+        } = self;
+        let TreeBuilder {
+            tree_name: b1,
+            db: b2,
+            unique_fields: b3,
+            index_fields: b4,
+            id_strategy: b5,
+            migrations: b6,
+        } = other;
+
+        a3.extend(b3.into_iter().filter(|x| !a3.contains(x)).collect::<Vec<_>>());
+        a4.extend(b4.into_iter().filter(|x| !a4.contains(x)).collect::<Vec<_>>());
 
-        TreeBuilder { tree_name: a1.or(b1), db: a2.or(b2) }
+        TreeBuilder {
+            tree_name: a1.or(b1),
+            db: a2.or(b2),
+            unique_fields: a3,
+            index_fields: a4,
+            id_strategy: a5.or(b5),
+            migrations: a6.merge(b6),
+        }
     }
 
     /// Set the name for this `Tree`
@@ -48,13 +714,100 @@ impl TreeBuilder {
         self
     }
 
+    /// Add a unique field, values for which are provided by `DocumentLike::unique_keys`
+    pub fn with_unique_field<I: Into<String>>(mut self, field_name: I) -> Self {
+        self.unique_fields.push(field_name.into());
+        self
+    }
+
+    /// Add a sled index field, values for which are provided by `DocumentLike::sled_index_keys`
+    pub fn with_sled_index<I: Into<String>>(mut self, field_name: I) -> Self {
+        self.index_fields.push(field_name.into());
+        self
+    }
+
+    /// Set how `id`s are generated for new `Document`s
+    ///
+    /// By default will use `IdStrategy::Sled`.
+    pub fn with_id_strategy(mut self, id_strategy: IdStrategy) -> Self {
+        self.id_strategy = Some(id_strategy);
+        self
+    }
+
+    /// Add a migration of stored `Document`s from schema version `from` to `from + 1`, see
+    /// `Migrations`
+    pub fn with_migration<A, B, F>(mut self, from: u64, f: F) -> Self
+    where
+        A: serde::de::DeserializeOwned,
+        B: serde::Serialize,
+        F: Fn(A) -> B + 'static,
+    {
+        self.migrations = self.migrations.with_migration(from, f);
+        self
+    }
+
+    /// Set the migrations of stored `Document`s, see `Migrations`
+    pub fn with_migrations(mut self, migrations: Migrations) -> Self {
+        self.migrations = migrations;
+        self
+    }
+
     /// Convert into finished `Tree`
     pub fn finish(self) -> err::Result<Tree> {
         let db = self.db.ok_or_else(|| err::custom("`db` not set"))?;
         let tree_name = self.tree_name.ok_or_else(|| err::custom("`tree_name` not set"))?;
 
         let inner = db.open_tree(tree_name.as_bytes())?;
+        let pending = db.open_tree(format!("{}/__pending__", tree_name).as_bytes())?;
+        let versions = db.open_tree(format!("{}/__versions__", tree_name).as_bytes())?;
+        let meta = db.open_tree(format!("{}/__meta__", tree_name).as_bytes())?;
+        let changes = db.open_tree(format!("{}/__changes__", tree_name).as_bytes())?;
+
+        // Changes are only delivered to `Watch`ers in this process, so any left over are stale.
+        changes.clear()?;
+
+        let unique = self
+            .unique_fields
+            .into_iter()
+            .map(|field_name| {
+                let tree =
+                    db.open_tree(format!("{}/__unique__/{}", tree_name, field_name).as_bytes())?;
+                Ok((field_name, tree))
+            })
+            .collect::<err::Result<Vec<_>>>()?;
+
+        let indexes = self
+            .index_fields
+            .into_iter()
+            .map(|field_name| {
+                let tree =
+                    db.open_tree(format!("{}/__index__/{}", tree_name, field_name).as_bytes())?;
+                Ok((field_name, tree))
+            })
+            .collect::<err::Result<Vec<_>>>()?;
+
+        let id_strategy = self.id_strategy.unwrap_or_default();
+
+        let tree = Tree {
+            inner,
+            id_strategy,
+            pending,
+            versions,
+            meta,
+            changes,
+            watchers: Arc::new(AtomicUsize::new(0)),
+            unique,
+            indexes,
+        };
+
+        tree.migrate_keys(&db.open_tree(format!("{}/__migrate__", tree_name).as_bytes())?)?;
+
+        tree.migrate_documents(&self.migrations)?;
+
+        if let IdStrategy::Monotonic = tree.id_strategy {
+            tree.init_next_id()?;
+        }
 
-        Ok(Tree { inner })
+        Ok(tree)
     }
 }
@@ -0,0 +1,176 @@
+/// Items that can be encoded as keys in a secondary index tree
+///
+/// Encodings are order-preserving and self-delimiting, so encoded keys sort in the same order as
+/// the values, and can be followed by other data (e.g. the `Document` `id`).
+pub trait IndexKey {
+    /// Append the encoded key to `out`.
+    fn encode_key(&self, out: &mut Vec<u8>);
+
+    /// Return the encoded key.
+    fn to_key(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode_key(&mut out);
+        out
+    }
+}
+
+macro_rules! impl_unsigned {
+    ($($ty:ty),*) => {
+        $(
+            impl IndexKey for $ty {
+                fn encode_key(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_be_bytes());
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_signed {
+    ($($ty:ty => $uty:ty),*) => {
+        $(
+            impl IndexKey for $ty {
+                fn encode_key(&self, out: &mut Vec<u8>) {
+                    let flipped = (*self as $uty) ^ (1 << (<$uty>::BITS - 1));
+                    out.extend_from_slice(&flipped.to_be_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned!(u8, u16, u32, u64, u128, usize);
+impl_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+
+impl IndexKey for f64 {
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        let bits = self.to_bits();
+        let flipped = if bits >> 63 == 1 { !bits } else { bits ^ (1 << 63) };
+        out.extend_from_slice(&flipped.to_be_bytes());
+    }
+}
+
+impl IndexKey for f32 {
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        let bits = self.to_bits();
+        let flipped = if bits >> 31 == 1 { !bits } else { bits ^ (1 << 31) };
+        out.extend_from_slice(&flipped.to_be_bytes());
+    }
+}
+
+impl IndexKey for bool {
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+}
+
+impl IndexKey for char {
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        (*self as u32).encode_key(out);
+    }
+}
+
+impl IndexKey for [u8] {
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        // `0x00` is escaped as `0x00 0xFF`, and the key is terminated with `0x00 0x00`.
+        for byte in self {
+            out.push(*byte);
+            if *byte == 0 {
+                out.push(0xFF);
+            }
+        }
+        out.extend_from_slice(&[0, 0]);
+    }
+}
+
+impl IndexKey for str {
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        self.as_bytes().encode_key(out);
+    }
+}
+
+impl IndexKey for String {
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        self.as_str().encode_key(out);
+    }
+}
+
+impl IndexKey for tantivy::DateTime {
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        self.timestamp().encode_key(out);
+        self.timestamp_subsec_nanos().encode_key(out);
+    }
+}
+
+impl<K: IndexKey + ?Sized> IndexKey for &K {
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        (**self).encode_key(out);
+    }
+}
+
+/// Field values that produce zero or more secondary index keys
+///
+/// Implemented for all `IndexKey` types, `Option` (`None` is not indexed), and collections
+/// (one key per element).
+// For use primarily by `pallet_macros`.
+#[doc(hidden)]
+pub trait FieldKeys {
+    fn field_keys(&self) -> Vec<Vec<u8>>;
+}
+
+macro_rules! impl_field_keys {
+    ($($ty:ty),*) => {
+        $(
+            impl FieldKeys for $ty {
+                fn field_keys(&self) -> Vec<Vec<u8>> {
+                    vec![self.to_key()]
+                }
+            }
+        )*
+    };
+}
+
+impl_field_keys!(
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    bool,
+    char,
+    String,
+    tantivy::DateTime
+);
+
+impl<K: IndexKey> FieldKeys for Option<K> {
+    fn field_keys(&self) -> Vec<Vec<u8>> {
+        self.iter().map(IndexKey::to_key).collect()
+    }
+}
+
+impl<K: IndexKey> FieldKeys for Vec<K> {
+    fn field_keys(&self) -> Vec<Vec<u8>> {
+        self.iter().map(IndexKey::to_key).collect()
+    }
+}
+
+impl<K: IndexKey, S> FieldKeys for std::collections::HashSet<K, S> {
+    fn field_keys(&self) -> Vec<Vec<u8>> {
+        self.iter().map(IndexKey::to_key).collect()
+    }
+}
+
+impl<K: IndexKey> FieldKeys for std::collections::BTreeSet<K> {
+    fn field_keys(&self) -> Vec<Vec<u8>> {
+        self.iter().map(IndexKey::to_key).collect()
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{db, err, Document};
+use std::marker::PhantomData;
+use std::ops::Bound;
+
+/**
+Lazy iterator over `Document`s in `id` order, as returned by `Store::iter` and `Store::range`
+
+Each `Document` is deserialized as it is reached, so collections larger than memory can be
+processed. Iteration errors are returned rather than skipped. Supports iterating in reverse with
+`Iterator::rev`.
+
+To resume from a cursor (e.g. the last `id` processed), use `Store::range` with an excluded start
+bound.
+
+## Usage:
+
+```rust
+use pallet::{err, DocumentLike, Store};
+use std::ops::Bound;
+
+fn process_batch<T: DocumentLike>(store: &Store<T>, cursor: Option<u64>) -> err::Result<Option<u64>> {
+    let start = cursor.map(Bound::Excluded).unwrap_or(Bound::Unbounded);
+
+    let mut last = None;
+    for doc in store.range((start, Bound::Unbounded)).take(100) {
+        let doc = doc?;
+        // ... process `doc`
+        last = Some(doc.id);
+    }
+
+    Ok(last)
+}
+```
+*/
+pub struct Iter<'a, T> {
+    tree: &'a db::Tree,
+    entries: sled::Iter,
+    versions: sled::Iter,
+    // The next unpaired entry of `versions` from the front and back, as `(id, version)`.
+    lookahead: [Option<(u64, u64)>; 2],
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: serde::de::DeserializeOwned> Iter<'a, T> {
+    /// Iterate over the `Document`s with keys in `range`.
+    pub(crate) fn new(tree: &'a db::Tree, range: (Bound<[u8; 8]>, Bound<[u8; 8]>)) -> Self {
+        Iter {
+            tree,
+            entries: tree.range(range),
+            versions: tree.versions.range(range),
+            lookahead: [None, None],
+            marker: PhantomData,
+        }
+    }
+
+    // Versions are read from a second iterator over the versions tree, kept one entry ahead of
+    // `entries`, so each version is read before the value it is paired with and can only be older
+    // than it (which `Store::update_if` treats as a conflict). If the versions tree has entries
+    // for `id`s with no value, e.g. deleted `Document`s, the version has to be read after the
+    // value, so both are read again with `Tree::get_versioned`.
+    fn step(&mut self, back: bool) -> Option<err::Result<Document<T>>> {
+        let Iter { tree, entries, versions, lookahead, .. } = self;
+
+        let mut next_version = || -> err::Result<Option<(u64, u64)>> {
+            let entry = if back { versions.next_back() } else { versions.next() };
+            entry
+                .map(|res| -> err::Result<_> {
+                    let (k, v) = res?;
+                    Ok((db::key_id(&k)?, db::key_id(&v)?))
+                })
+                .transpose()
+        };
+
+        // Whether the versions entry for `a` is reached after the entry for `b`.
+        let after = |a: u64, b: u64| if back { a < b } else { a > b };
+
+        let lookahead = &mut lookahead[back as usize];
+
+        // `Some(None)` if the `Document` was removed since its key was read.
+        let mut load = || -> err::Result<Option<Option<Document<T>>>> {
+            if lookahead.is_none() {
+                *lookahead = next_version()?;
+            }
+
+            let (key, value) = match if back { entries.next_back() } else { entries.next() } {
+                Some(entry) => entry?,
+                None => return Ok(None),
+            };
+            let id = db::key_id(&key)?;
+
+            let (value, version) = match *lookahead {
+                Some((version_id, version)) if version_id == id => {
+                    *lookahead = None;
+                    (value, version)
+                }
+                Some((version_id, _)) if !after(version_id, id) => {
+                    *lookahead = loop {
+                        match next_version()? {
+                            Some((version_id, _)) if !after(version_id, id) => continue,
+                            next => break next,
+                        }
+                    };
+                    match tree.get_versioned(id)? {
+                        Some(versioned) => versioned,
+                        None => return Ok(Some(None)),
+                    }
+                }
+                _ => (value, 0),
+            };
+
+            Ok(Some(Some(Document {
+                id,
+                inner: crate::serialize::deserialize(&value)?,
+                version: Some(version),
+            })))
+        };
+
+        loop {
+            match load().transpose()? {
+                Ok(Some(doc)) => return Some(Ok(doc)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+impl<T: serde::de::DeserializeOwned> Iterator for Iter<'_, T> {
+    type Item = err::Result<Document<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.step(false)
+    }
+}
+
+impl<T: serde::de::DeserializeOwned> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.step(true)
+    }
+}
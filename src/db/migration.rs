@@ -0,0 +1,90 @@
+use crate::err;
+use std::collections::BTreeMap;
+
+// Converts a serialized `Document` from one schema version to the next.
+type Step = Box<dyn Fn(&[u8]) -> err::Result<Vec<u8>>>;
+
+/**
+Migrations of stored `Document`s between schema versions
+
+Each migration converts a `Document` from schema version `n` to `n + 1`, and the schema version
+of the `Tree` is the version after the last migration (or `0` if there are none). When a `Tree`
+is opened with a stored version lower than this, the outstanding migrations are run in order on
+every stored `Document`, and the search index is rebuilt. New (empty) trees start at the latest
+version, and trees written before schema versions were recorded start at version `0`.
+
+Trees written by earlier versions of `pallet` store little-endian `id` keys, which are
+converted to big-endian (for `id` ordered iteration) when first opened. The conversion runs once,
+before any other migrations, and converted trees can no longer be read by those earlier versions.
+
+## Usage:
+
+```rust
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BookV0 {
+    title: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BookV1 {
+    title: String,
+    subtitle: Option<String>,
+}
+
+let migrations = pallet::db::Migrations::new()
+    .with_migration(0, |old: BookV0| BookV1 { title: old.title, subtitle: None });
+
+assert_eq!(migrations.version(), 1);
+```
+*/
+#[derive(Default)]
+pub struct Migrations {
+    steps: BTreeMap<u64, Step>,
+}
+
+impl Migrations {
+    /// Create an empty set of migrations
+    pub fn new() -> Self {
+        Migrations::default()
+    }
+
+    pub(crate) fn merge(mut self, other: Self) -> Self {
+        for (from, step) in other.steps {
+            self.steps.entry(from).or_insert(step);
+        }
+        self
+    }
+
+    /// Add a migration from schema version `from` to `from + 1`, converting each `Document`
+    /// stored as `A` to `B`.
+    pub fn with_migration<A, B, F>(mut self, from: u64, f: F) -> Self
+    where
+        A: serde::de::DeserializeOwned,
+        B: serde::Serialize,
+        F: Fn(A) -> B + 'static,
+    {
+        let step = move |bytes: &[u8]| {
+            let old = crate::serialize::deserialize(bytes)?;
+            crate::serialize::serialize(&f(old))
+        };
+        self.steps.insert(from, Box::new(step));
+        self
+    }
+
+    /// The schema version reached once all migrations have run.
+    pub fn version(&self) -> u64 {
+        self.steps.keys().next_back().map(|from| from + 1).unwrap_or(0)
+    }
+
+    pub(crate) fn step(&self, from: u64) -> err::Result<&Step> {
+        self.steps
+            .get(&from)
+            .ok_or_else(|| err::custom(format!("No migration from schema version `{}`", from)))
+    }
+}
+
+impl std::fmt::Debug for Migrations {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Migrations").field("versions", &self.steps.keys()).finish()
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{err, Document};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// How often the thread started by `Watch::into_channel` checks whether its receiver was dropped.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// A change recorded in the change feed tree, with serialized values.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct ChangeRecord {
+    pub(crate) id: u64,
+    pub(crate) version: Option<u64>,
+    pub(crate) old: Option<Vec<u8>>,
+    pub(crate) new: Option<Vec<u8>>,
+}
+
+/// A change to a `Document`, as returned by `Watch`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub enum Event<T> {
+    Created(Document<T>),
+    Updated { old: Document<T>, new: Document<T> },
+    Deleted(u64),
+}
+
+/**
+Blocking iterator over changes made to a `Store`, as returned by `Store::watch`
+
+Built on `sled::Tree::watch_prefix`, so only sees changes made in this process, through the same
+`Store`, after the `Watch` was created. Changes made in a transaction are seen once it commits.
+
+## Usage:
+
+```rust
+use pallet::{db, err, DocumentLike, Store};
+
+fn log_changes<T>(store: &Store<T>) -> err::Result<()>
+where
+    T: DocumentLike + std::fmt::Debug,
+{
+    for event in store.watch().take(10) {
+        match event? {
+            db::Event::Created(doc) => println!("created {:?}", doc),
+            db::Event::Updated { old, new } => println!("updated {:?} to {:?}", old, new),
+            db::Event::Deleted(id) => println!("deleted {}", id),
+        }
+    }
+    Ok(())
+}
+```
+*/
+pub struct Watch<T> {
+    pub(crate) subscriber: sled::Subscriber,
+    pub(crate) watchers: Arc<AtomicUsize>,
+    pub(crate) marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Watch<T>
+where
+    T: serde::de::DeserializeOwned + Send + 'static,
+{
+    /// Forward events to a channel from a background thread.
+    ///
+    /// The thread stops shortly after the `WatchReceiver` is dropped, even if there are no more
+    /// events.
+    pub fn into_channel(mut self) -> WatchReceiver<T> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        let alive = Arc::new(());
+        let weak = Arc::downgrade(&alive);
+
+        std::thread::spawn(move || {
+            while weak.strong_count() > 0 {
+                match self.next_timeout(POLL_INTERVAL) {
+                    Ok(event) => {
+                        if sender.send(event).is_err() {
+                            break;
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        });
+
+        WatchReceiver { receiver, _alive: alive }
+    }
+}
+
+impl<T: serde::de::DeserializeOwned> Watch<T> {
+    // Wait up to `timeout` for the next event, skipping removals as `next` does.
+    fn next_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<err::Result<Event<T>>, RecvTimeoutError> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+
+            if let sled::Event::Insert { value, .. } = self.subscriber.next_timeout(remaining)? {
+                if let Some(res) = Self::to_event(&value).transpose() {
+                    return Ok(res);
+                }
+            }
+        }
+    }
+
+    fn to_event(record: &[u8]) -> err::Result<Option<Event<T>>> {
+        let ChangeRecord { id, version, old, new } = crate::serialize::deserialize(record)?;
+
+        let to_document = |bytes: &[u8], version: Option<u64>| -> err::Result<_> {
+            Ok(Document { id, inner: crate::serialize::deserialize(bytes)?, version })
+        };
+
+        Ok(match (old, new) {
+            (None, Some(new)) => Some(Event::Created(to_document(&new, version)?)),
+            (Some(old), Some(new)) => Some(Event::Updated {
+                old: to_document(&old, version.and_then(|x| x.checked_sub(1)))?,
+                new: to_document(&new, version)?,
+            }),
+            (Some(_), None) => Some(Event::Deleted(id)),
+            (None, None) => None,
+        })
+    }
+}
+
+impl<T: serde::de::DeserializeOwned> Iterator for Watch<T> {
+    type Item = err::Result<Event<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Removals are recorded changes being cleared, and are skipped.
+        for event in &mut self.subscriber {
+            if let sled::Event::Insert { value, .. } = event {
+                if let Some(res) = Self::to_event(&value).transpose() {
+                    return Some(res);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Receiver of events forwarded by `Watch::into_channel`, derefs to a `std::sync::mpsc::Receiver`
+pub struct WatchReceiver<T> {
+    receiver: Receiver<err::Result<Event<T>>>,
+    // Dropped with the receiver, which stops the forwarding thread.
+    _alive: Arc<()>,
+}
+
+impl<T> std::ops::Deref for WatchReceiver<T> {
+    type Target = Receiver<err::Result<Event<T>>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.receiver
+    }
+}
+
+impl<T> Drop for Watch<T> {
+    fn drop(&mut self) {
+        self.watchers.fetch_sub(1, Ordering::SeqCst);
+    }
+}
@@ -63,15 +63,98 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 See the example for usage. The following attributes can be used to customize the implementation:
 
 * `tree_name`: A container level attribute to specify the `sled::Tree` name.
+* `fields_struct`, `query_struct`: Container level attributes to rename the generated index fields
+  and query helper structs, see below.
 * `index_field_name`: Rename the field in the search schema.
 * `index_field_type`: Set the index field type, must implement `Into<tantivy::schema::Value>`.
+  `Vec`, `HashSet` and `BTreeSet` fields are indexed as multi-valued fields, with one value per
+  element (and multi-valued fast fields if their options include `tantivy::schema::FAST`).
 * `index_field_options`: Set the index field options. By default, the options for `String` is
-`tantivy::schema::TEXT`, and the options for numeric types is `tantivy::schema::INDEXED`.
+  `tantivy::schema::TEXT`, and the options for numeric types is `tantivy::schema::INDEXED`.
+  Numeric and date fields used with `search::Sorted` or aggregations (e.g. `search::Stats`) must
+  include `tantivy::schema::FAST`.
 * `default_search_field`: Include this field in the list of default search fields.
+* `facet`: Index this field as a hierarchical facet (e.g. `/category/sub`), shortcut for
+  `index_field_type = "pallet::search::Facet"`. `Option`, `Vec`, `HashSet` and `BTreeSet` fields
+  are indexed as optional or multi-valued facets. Facet counts are available via `search::Faceted`.
+* `suggest`: Complete terms of this text field with `Store::suggest`.
 * `skip_indexing`: Do not index this field.
+* `unique`: Keep a `sled` tree mapping this field's value to the `Document` `id`, updated in the
+  same transaction as each write. Writes fail with `err::Error::UniqueConflict` if another
+  `Document` has the same value, and `Store::find_by_unique` looks up by value. `Option` fields
+  are only checked when `Some`, and collection fields are checked per element.
+* `sled_index`: Keep an ordered `sled` tree of this field's values and `Document` `id`s, updated
+  in the same transaction as each write, for exact lookups with `Store::find_by` and range scans
+  with `Store::range_by`. Values must implement `db::IndexKey`.
+
+The derive also generates a struct of index fields (e.g. `BookFields`, with a
+`tantivy::schema::Field` per indexed field) used as `DocumentLike::IndexFieldsType`, and query
+helpers for each indexed field returning `search::TypedField`s, e.g.
+`store.search(&Book::q().rating().gt(8))`, on a generated struct (e.g. `BookQuery`) returned by
+`Book::q()`. Both structs are declared alongside the deriving struct with its visibility, so can
+be renamed with e.g. `#[pallet(fields_struct = "BookIndexFields", query_struct = "BookQ")]` if
+the default names are already in use.
 
 # Changelog
 
+## Unreleased
+
+* Add `search::Paged` searcher for offset/limit pagination, with `search::Page` shared by every
+  paginated searcher and defaulting to the first 10 results
+* Add `search::Sorted` searcher to order results by fast field values
+* Add `search::Facet` field type, `facet` attribute and `search::Faceted` searcher
+* Support `Vec`, `HashSet` and `BTreeSet` fields as multi-valued index fields
+* Apply index changes after the `sled` transaction commits, instead of inside it, indexing the
+  latest stored value of each changed `Document` so concurrent writes cannot leave it stale
+* Log pending index changes and the last commit opstamp in `sled`, and replay or rebuild the
+  index on `StoreBuilder::finish` if they are out of sync
+* Add `Store::verify` and `Store::repair` to check and fix index consistency
+* Add `CommitPolicy` to batch index commits, and `Store::commit`
+* Keep a long-lived `tantivy::IndexReader` on `search::Index`, reloaded after each commit, with
+  `IndexBuilder::with_reload_policy`
+* Add `search::Highlighted` searcher to attach highlighted snippets to hits
+* Add `unique` attribute for unique secondary indexes, `Store::find_by_unique` and
+  `err::Error::UniqueConflict`
+* Add `sled_index` attribute for ordered secondary indexes, with `Store::find_by` and
+  `Store::range_by`; secondary trees are built from existing `Document`s when first opened
+* Add lazy `Store::iter`, `Store::iter_rev` and `Store::range`; `Store::all` no longer skips
+  iteration errors
+* **Breaking:** store tree keys as big-endian `id`s, so that iteration is in `id` order; trees
+  written by earlier versions are converted when first opened, recording a key format version,
+  after which earlier versions cannot read them
+* Add `Document::version` for optimistic concurrency, `Store::update_if` and
+  `err::Error::VersionConflict`; `Store::update` fails if the version of a loaded `Document` no
+  longer matches, and versions keep increasing when an `id` is deleted and written again
+* **Breaking:** `Document` has a private version field, so must be created with `Document::new`
+  instead of a struct literal
+* Add `Store::update_with` for atomic read-modify-write updates
+* Add `Store::insert_with_id` and `Store::upsert` for caller-supplied `id`s,
+  `err::Error::AlreadyExists`, and `db::IdStrategy` with `TreeBuilder::with_id_strategy`
+* Add `Transaction` for atomic changes across several `Store`s sharing a `sled::Db`
+* Add `Store::watch` for a feed of `db::Event`s as `Document`s are created, updated and deleted
+* Add `async` feature with `AsyncStore`, running blocking work on its own `rayon` thread pool and
+  batching queued writes on a writer thread, and `err::Error::Shared` for errors returned to
+  every write in a batch
+* Add `db::Migrations` to convert stored `Document`s between schema versions, with
+  `StoreBuilder::with_migration`; the search index is rebuilt after a migration
+* Add `err::Error::SchemaMismatch` listing each `search::SchemaDifference` when an existing index
+  has a different schema, and `SchemaMismatchPolicy` with
+  `StoreBuilder::with_schema_mismatch_policy` to recreate and re-index it instead
+* Add `search::Query` builder for term, phrase, range, fuzzy, prefix, boolean and boosted queries
+  with fields referenced by name, as an alternative to query strings
+* **Breaking:** `pallet_macros` generates a typed fields struct (e.g. `BookFields`) as the
+  `IndexFieldsType`, and `search::TypedField` query helpers for each indexed field on a query
+  struct (e.g. `BookQuery`, returned by `Book::q()`); these names, and the `q` associated
+  function, must not already be in use, or can be renamed with the `fields_struct` and
+  `query_struct` attributes
+* Add `search::Stats`, `search::Histogram`, `search::DateHistogram` and `search::Terms`
+  aggregation collectors over fast and facet fields by name, and `search::Aggregated` searcher
+  returning `search::AggregatedResults`
+* Add `search::Fuzzy` typo-tolerant query over default search fields, with per-length edit
+  distances and optional prefix matching of the last term
+* Add `Store::suggest` for search-as-you-type completions from the terms of fields marked with
+  the `suggest` attribute or `search::IndexBuilder::with_suggest_field`
+
 ## 0.7.0
 
 * Update sled/tantivy deps (thanks @lberezy)
@@ -101,24 +184,26 @@ See the example for usage. The following attributes can be used to customize the
 
 */
 
-use std::convert::TryInto;
 use std::marker::PhantomData;
 use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Instant;
 
 /// Re-export the `pallet_macros` derive type.
 pub use pallet_macros::DocumentLike;
 
 /// Re-exports `tantivy` and `sled` for use by `pallet_macros` and convenience.
 pub mod ext {
+    pub use rayon;
     pub use sled;
     pub use tantivy;
 }
 
 #[cfg(feature = "bincode")]
 mod serialize {
-    pub fn serialize<T: ?Sized>(value: &T) -> crate::err::Result<Vec<u8>>
+    pub fn serialize<T>(value: &T) -> crate::err::Result<Vec<u8>>
     where
-        T: serde::Serialize,
+        T: serde::Serialize + ?Sized,
     {
         bincode::serialize(value).map_err(crate::err::Error::Bincode)
     }
@@ -164,6 +249,17 @@ pub mod err {
         #[cfg(feature = "serde_cbor")]
         #[error("De/serialization error: `{0}`")]
         CBOR(#[from] serde_cbor::Error),
+        #[error("Unique field `{field}` conflicts with document `{id}`")]
+        UniqueConflict { field: String, id: u64 },
+        #[error("Document `{id}` already exists")]
+        AlreadyExists { id: u64 },
+        #[error("Document `{id}` has version `{actual:?}`, expected `{expected}`")]
+        VersionConflict { id: u64, expected: u64, actual: Option<u64> },
+        #[error("Index schema does not match: {}", display_list(.differences))]
+        SchemaMismatch { differences: Vec<crate::search::SchemaDifference> },
+        /// The same error returned to several callers, e.g. each write in an `AsyncStore` batch.
+        #[error(transparent)]
+        Shared(std::sync::Arc<Error>),
         #[error("Error: {0}")]
         Custom(Box<str>),
     }
@@ -195,6 +291,10 @@ pub mod err {
         }
     }
 
+    fn display_list<T: std::fmt::Display>(items: &[T]) -> String {
+        items.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    }
+
     /// Create a custom error.
     pub fn custom<T: std::fmt::Display>(t: T) -> Error {
         Error::Custom(t.to_string().into_boxed_str())
@@ -209,11 +309,47 @@ pub mod db;
 /// Items relating to `tantivy` and searching
 pub mod search;
 
-/// Persisted wrapper of the internal document, includes `id`.
+mod transaction;
+
+#[cfg(feature = "async")]
+mod async_store;
+
+#[cfg(feature = "async")]
+pub use async_store::AsyncStore;
+
+pub use transaction::{Transaction, TransactionContext, TransactionResult, TransactionStore};
+
+/// Persisted wrapper of the internal document, includes `id` and version.
+///
+/// Create with `Document::new`, or load from the `Store`.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Document<T> {
     pub id: u64,
     pub inner: T,
+    #[serde(default)]
+    version: Option<u64>,
+}
+
+impl<T> Document<T> {
+    /// Create a new unversioned `Document`.
+    pub fn new(id: u64, inner: T) -> Self {
+        Document { id, inner, version: None }
+    }
+
+    /// The version when loaded from the `Store`, incremented on every update.
+    ///
+    /// If set, `Store::update` and `Store::upsert` fail with `err::Error::VersionConflict` unless
+    /// it matches the stored version.
+    pub fn version(&self) -> Option<u64> {
+        self.version
+    }
+
+    /// Set the version expected by `Store::update` and `Store::upsert`, or `None` to overwrite
+    /// regardless.
+    pub fn with_version(mut self, version: Option<u64>) -> Self {
+        self.version = version;
+        self
+    }
 }
 
 impl<T> std::ops::Deref for Document<T> {
@@ -235,124 +371,195 @@ pub struct Store<T: DocumentLike> {
     tree: db::Tree,
     marker: PhantomData<fn(T)>,
     pub index: search::Index<T::IndexFieldsType>,
+    commit_policy: CommitPolicy,
+    commit_state: Mutex<CommitState>,
 }
 
-impl<T: DocumentLike> Store<T> {
-    /// Create a new builder
-    pub fn builder() -> StoreBuilder<T> {
-        StoreBuilder::default()
-    }
+/// When changes are committed to the search index.
+///
+/// Uncommitted changes are visible to `Store::find` and `Store::all`, but not to searches.
+/// Except with `CommitPolicy::Manual`, any changes not yet committed when the `Store` is dropped
+/// are committed then, blocking the drop until the commit finishes. Changes that are still
+/// uncommitted (if that commit fails, or the process dies first) are replayed on the next
+/// `StoreBuilder::finish`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CommitPolicy {
+    /// Commit after every write operation (the default).
+    #[default]
+    EveryWrite,
+    /// Commit once at least this many `Document` changes are uncommitted.
+    Operations(usize),
+    /// Commit on the first write once this much time has passed since the last commit.
+    ///
+    /// There is no background timer: if writes stop, the last changes are not searchable until
+    /// the next write or a call to `Store::commit`, so call it periodically if writes may pause.
+    Interval(std::time::Duration),
+    /// Only commit when `Store::commit` is called, including before the `Store` is dropped.
+    Manual,
+}
 
-    /// Create a new `Document`, returns the persisted document's `id`.
-    pub fn create(&self, inner: &T) -> err::Result<u64> {
-        let id = self.tree.transaction(
-            |tree| -> sled::transaction::ConflictableTransactionResult<u64, err::Error> {
-                self.index.with_writer(|index_writer| {
-                    let id = tree.generate_id()?;
+/// What `StoreBuilder::finish` does when the existing search index has a different schema.
+///
+/// The index is always recreated after a migration has run, see `db::Migrations`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchemaMismatchPolicy {
+    /// Fail with `err::Error::SchemaMismatch`, listing each difference (the default).
+    #[default]
+    Fail,
+    /// Recreate the index with the new schema, and re-index all `Document`s from the tree.
+    Recreate,
+}
 
-                    let serialized_inner = crate::serialize::serialize(inner)
-                        .map_err(sled::transaction::ConflictableTransactionError::Abort)?;
+// Changes applied to the index writer, but not yet committed.
+//
+// `replay` is set when uncommitted changes were rolled back after an error, so that their logged
+// entries are applied again by the next write or commit.
+struct CommitState {
+    pending: Vec<(u64, u64)>,
+    uncommitted: usize,
+    last_commit: Instant,
+    replay: bool,
+}
 
-                    let mut search_doc = inner
-                        .as_index_document(&self.index.fields)
-                        .map_err(sled::transaction::ConflictableTransactionError::Abort)?;
+impl Default for CommitState {
+    fn default() -> Self {
+        CommitState {
+            pending: Vec::new(),
+            uncommitted: 0,
+            last_commit: Instant::now(),
+            replay: false,
+        }
+    }
+}
 
-                    search_doc.add_u64(self.index.id_field, id);
+impl CommitState {
+    // Whether there are changes to commit, or rolled back changes to replay.
+    fn has_changes(&self) -> bool {
+        self.replay || self.uncommitted > 0 || !self.pending.is_empty()
+    }
+}
 
-                    index_writer.add_document(search_doc);
+impl CommitPolicy {
+    fn should_commit(&self, state: &CommitState) -> bool {
+        match self {
+            CommitPolicy::EveryWrite => true,
+            CommitPolicy::Operations(operations) => state.uncommitted >= *operations,
+            CommitPolicy::Interval(interval) => state.last_commit.elapsed() >= *interval,
+            CommitPolicy::Manual => false,
+        }
+    }
+}
 
-                    tree.insert(&id.to_le_bytes(), serialized_inner)?;
+// A `Document` serialized and converted ahead of a write.
+struct PreparedDocument {
+    serialized: Vec<u8>,
+    search_doc: tantivy::Document,
+    secondary_keys: db::SecondaryKeys,
+}
 
-                    index_writer
-                        .commit()
-                        .map_err(err::Error::Tantivy)
-                        .map_err(sled::transaction::ConflictableTransactionError::Abort)?;
+// A change to the search index, applied after the matching tree change is persisted.
+//
+// Changes are resolved against the tree under the index writer lock, so that whatever order
+// concurrent writes to an `id` are applied in, the last one applied indexes its latest value.
+enum IndexChange {
+    // A written `Document`, indexed as-is if it is still the stored value.
+    Upsert(u64, PreparedDocument),
+    // Index the stored value for an `id`, or remove it from the index if there is none.
+    Refresh(u64),
+    // Replace the whole index with the stored `Document`s.
+    Reindex,
+}
 
-                    Ok(id)
-                })
-            },
-        )?;
+impl<T: DocumentLike> Store<T> {
+    /// Create a new builder
+    pub fn builder() -> StoreBuilder<T> {
+        StoreBuilder::default()
+    }
 
-        Ok(id)
+    /// Create a new `Document`, returns the persisted document's `id`.
+    pub fn create(&self, inner: &T) -> err::Result<u64> {
+        let ids = self.create_multi(std::slice::from_ref(inner))?;
+        Ok(ids[0])
     }
 
     /// Create new `Document`s, returns the persisted documents' `id`s.
     pub fn create_multi(&self, inners: &[T]) -> err::Result<Vec<u64>> {
-        let ids = self.tree.transaction(
-            |tree| -> sled::transaction::ConflictableTransactionResult<_, err::Error> {
-                self.index.with_writer(|index_writer| {
-                    let mut out = Vec::with_capacity(inners.len());
-                    for inner in inners {
-                        let id = tree.generate_id()?;
-
-                        let serialized_inner = crate::serialize::serialize(inner)
-                            .map_err(sled::transaction::ConflictableTransactionError::Abort)?;
-
-                        let mut search_doc = inner
-                            .as_index_document(&self.index.fields)
-                            .map_err(sled::transaction::ConflictableTransactionError::Abort)?;
-
-                        search_doc.add_u64(self.index.id_field, id);
-
-                        index_writer.add_document(search_doc);
-
-                        tree.insert(&id.to_le_bytes(), serialized_inner)?;
-
-                        out.push(id);
-                    }
-
-                    index_writer
-                        .commit()
-                        .map_err(err::Error::Tantivy)
-                        .map_err(sled::transaction::ConflictableTransactionError::Abort)?;
+        let creates = inners.iter().map(|inner| (None, inner)).collect::<Vec<_>>();
+        self.write_creates(&creates)
+    }
 
-                    Ok(out)
-                })
-            },
-        )?;
+    /// Create a new `Document` with a caller-supplied `id`, e.g. when importing from another system.
+    ///
+    /// Fails with `err::Error::AlreadyExists` if the `id` is in use.
+    pub fn insert_with_id(&self, id: u64, inner: &T) -> err::Result<()> {
+        self.write_creates(&[(Some(id), inner)])?;
+        Ok(())
+    }
 
-        Ok(ids)
+    /// Create or replace a `Document` with a caller-supplied `id`, returns the new version.
+    ///
+    /// Fails with `err::Error::VersionConflict` if `doc.version()` is set and does not match the
+    /// stored version.
+    pub fn upsert(&self, doc: &Document<T>) -> err::Result<u64> {
+        let versions = self.write_updates(&[(doc.id, doc.version, &doc.inner)])?;
+        Ok(versions[0])
     }
 
     /// Update a given `Document`.
+    ///
+    /// Fails with `err::Error::VersionConflict` if `doc.version()` is set and does not match the
+    /// stored version, e.g. if it was updated since `doc` was loaded. Use `Document::new` (or
+    /// `Document::with_version(None)`) to overwrite regardless.
     pub fn update(&self, doc: &Document<T>) -> err::Result<()> {
         self.update_multi(std::slice::from_ref(doc))
     }
 
     /// Update given `Document`s.
+    ///
+    /// Fails without writing anything if any `Document`'s version is set and does not match, see
+    /// `update`.
     pub fn update_multi(&self, docs: &[Document<T>]) -> err::Result<()> {
-        self.tree.transaction(
-            |tree| -> sled::transaction::ConflictableTransactionResult<_, err::Error> {
-                self.index.with_writer(|index_writer| {
-                    for Document { id, inner } in docs {
-                        let serialized_inner = crate::serialize::serialize(inner)
-                            .map_err(sled::transaction::ConflictableTransactionError::Abort)?;
-
-                        let mut search_doc = inner
-                            .as_index_document(&self.index.fields)
-                            .map_err(sled::transaction::ConflictableTransactionError::Abort)?;
-
-                        search_doc.add_u64(self.index.id_field, *id);
-
-                        index_writer
-                            .delete_term(tantivy::Term::from_field_u64(self.index.id_field, *id));
-
-                        index_writer.add_document(search_doc);
+        let updates = docs.iter().map(|doc| (doc.id, doc.version, &doc.inner)).collect::<Vec<_>>();
+        self.write_updates(&updates)?;
+        Ok(())
+    }
 
-                        tree.insert(&id.to_le_bytes(), serialized_inner)?;
-                    }
+    /// Update a `Document` only if its stored version is `version`.
+    ///
+    /// Returns the new version, or `None` if the stored version did not match (or the `Document`
+    /// does not exist).
+    pub fn update_if(&self, id: u64, version: u64, inner: &T) -> err::Result<Option<u64>> {
+        match self.write_updates(&[(id, Some(version), inner)]) {
+            Ok(versions) => Ok(Some(versions[0])),
+            Err(err::Error::VersionConflict { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
 
-                    index_writer
-                        .commit()
-                        .map_err(err::Error::Tantivy)
-                        .map_err(sled::transaction::ConflictableTransactionError::Abort)?;
+    /// Update a `Document` in place, reading and writing it within one transaction.
+    ///
+    /// Returns the updated `Document`, or `None` if there is no `Document` with this `id`. `f` may
+    /// be called more than once if the transaction conflicts with another write.
+    ///
+    /// Only the tree write is atomic: the search index is updated once the transaction has
+    /// committed, with whichever value is stored by then, so concurrent updates leave it with the
+    /// latest value.
+    pub fn update_with<F>(&self, id: u64, f: F) -> err::Result<Option<Document<T>>>
+    where
+        F: FnMut(&mut T),
+    {
+        let f = std::cell::RefCell::new(f);
 
-                    Ok(())
-                })
-            },
-        )?;
+        let updated =
+            self.tree.transaction(|txn| self.txn_update_with(txn, id, &mut *f.borrow_mut()))?;
 
-        Ok(())
+        match updated {
+            Some((doc, prepared, entry)) => {
+                self.apply_index_changes(vec![IndexChange::Upsert(id, prepared)], &[entry])?;
+                Ok(Some(doc))
+            }
+            None => Ok(None),
+        }
     }
 
     /// Delete a `Document` by `id`.
@@ -362,27 +569,8 @@ impl<T: DocumentLike> Store<T> {
 
     /// Delete `Document`s by `id`s.
     pub fn delete_multi(&self, ids: &[u64]) -> err::Result<()> {
-        self.tree.transaction(
-            |tree| -> sled::transaction::ConflictableTransactionResult<_, err::Error> {
-                self.index.with_writer(|index_writer| {
-                    for id in ids {
-                        index_writer
-                            .delete_term(tantivy::Term::from_field_u64(self.index.id_field, *id));
-
-                        tree.remove(&id.to_le_bytes())?;
-                    }
-
-                    index_writer
-                        .commit()
-                        .map_err(err::Error::Tantivy)
-                        .map_err(sled::transaction::ConflictableTransactionError::Abort)?;
-
-                    Ok(())
-                })
-            },
-        )?;
-
-        Ok(())
+        let (changes, pending) = self.tree_deletes(ids)?;
+        self.apply_index_changes(changes, &pending)
     }
 
     /// Search the datastore, using the query language provided by `tantivy`.
@@ -390,49 +578,179 @@ impl<T: DocumentLike> Store<T> {
         searcher.search(self)
     }
 
+    /// Complete the last term of `prefix` with up to `limit` terms of a suggest field, most common
+    /// first, for search-as-you-type.
+    ///
+    /// `field_name` is the name of an index field marked `suggest` (see
+    /// `search::IndexBuilder::with_suggest_field`). Returns nothing if `prefix` ends with a
+    /// separator, and preceding text is kept as-is (e.g. `"old ma"` may complete to `"old man"`).
+    ///
+    /// Every indexed term starting with the last term of `prefix` is counted, so callers of large
+    /// indexes may want to wait for a few characters before suggesting.
+    pub fn suggest(
+        &self,
+        prefix: &str,
+        field_name: &str,
+        limit: usize,
+    ) -> err::Result<Vec<search::Suggestion>> {
+        self.index.suggest(prefix, field_name, limit)
+    }
+
     /// Get all `Documents` from the datastore. Does not use the search index.
+    ///
+    /// Collects every `Document` into memory, see `iter` for a lazy alternative.
     pub fn all(&self) -> err::Result<Vec<Document<T>>> {
-        Ok(self
-            .tree
-            .iter()
-            .flatten()
-            .map(|(k, v)| {
-                Ok(Document {
-                    id: u64::from_le_bytes(k.as_ref().try_into().map_err(err::custom)?),
-                    inner: crate::serialize::deserialize(&v)?,
-                })
-            })
-            .collect::<err::Result<Vec<_>>>()?)
+        self.iter().collect()
+    }
+
+    /// Iterate over all `Document`s in `id` order. Does not use the search index.
+    pub fn iter(&self) -> db::Iter<'_, T> {
+        use std::ops::Bound;
+
+        db::Iter::new(&self.tree, (Bound::Unbounded, Bound::Unbounded))
+    }
+
+    /// Iterate over all `Document`s in reverse `id` order. Does not use the search index.
+    pub fn iter_rev(&self) -> std::iter::Rev<db::Iter<'_, T>> {
+        self.iter().rev()
+    }
+
+    /// Iterate over the `Document`s with `id`s in `ids`, in `id` order. Does not use the search
+    /// index.
+    ///
+    /// Pass `(Bound::Excluded(cursor), Bound::Unbounded)` to resume after a previously seen `id`.
+    pub fn range<R: std::ops::RangeBounds<u64>>(&self, ids: R) -> db::Iter<'_, T> {
+        use std::ops::Bound;
+
+        let to_key_bound = |bound: Bound<&u64>| match bound {
+            Bound::Included(id) => Bound::Included(db::id_key(*id)),
+            Bound::Excluded(id) => Bound::Excluded(db::id_key(*id)),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        let range = (to_key_bound(ids.start_bound()), to_key_bound(ids.end_bound()));
+
+        db::Iter::new(&self.tree, range)
     }
 
     /// Index (or re-index) all `Documents` in the datastore.
+    ///
+    /// Replaces the whole search index, so entries for `Document`s no longer in the tree are
+    /// removed.
     pub fn index_all(&self) -> err::Result<()> {
-        self.index.with_writer(|index_writer| {
-            let docs = self.all()?;
-            for Document { id, inner } in docs {
-                let mut search_doc = inner.as_index_document(&self.index.fields)?;
+        let pending = self.tree.pending_entries()?;
 
-                search_doc.add_u64(self.index.id_field, id);
+        self.with_commit_state(|index_writer, commit_state| {
+            self.reindex_with_writer(index_writer)?;
 
-                index_writer.delete_term(tantivy::Term::from_field_u64(self.index.id_field, id));
+            commit_state.pending.extend(pending);
 
-                index_writer.add_document(search_doc);
-            }
+            self.commit_with_writer(index_writer, commit_state)
+        })
+    }
 
-            index_writer.commit()?;
+    /// Commit any outstanding changes to the search index.
+    ///
+    /// Only needed when using a `CommitPolicy` other than `CommitPolicy::EveryWrite`.
+    pub fn commit(&self) -> err::Result<()> {
+        // Avoids creating an index writer when there is nothing to commit.
+        if !self.commit_state.lock().map_err(err::custom)?.has_changes() {
+            return Ok(());
+        }
 
-            Ok(())
+        self.with_commit_state(|index_writer, commit_state| {
+            self.replay_rolled_back(index_writer, commit_state)?;
+
+            if commit_state.uncommitted == 0 && commit_state.pending.is_empty() {
+                return Ok(());
+            }
+
+            self.commit_with_writer(index_writer, commit_state)
         })
     }
 
     /// Find a single `Document` by its `id`. Does not use the search index.
     pub fn find(&self, id: u64) -> err::Result<Option<Document<T>>> {
-        Ok(self
-            .tree
-            .get(id.to_le_bytes())?
-            .map(|bytes| crate::serialize::deserialize(&bytes))
-            .transpose()?
-            .map(|inner| Document { id, inner }))
+        match self.tree.get_versioned(id)? {
+            Some((bytes, version)) => Ok(Some(Document {
+                id,
+                inner: crate::serialize::deserialize(&bytes)?,
+                version: Some(version),
+            })),
+            None => Ok(None),
+        }
+    }
+
+    /// Find a single `Document` by the value of a unique field. Does not use the search index.
+    ///
+    /// `field_name` is the name of a field marked `unique` (see `db::TreeBuilder::with_unique_field`).
+    pub fn find_by_unique<K>(&self, field_name: &str, value: &K) -> err::Result<Option<Document<T>>>
+    where
+        K: db::IndexKey + ?Sized,
+    {
+        match self.tree.unique_tree(field_name)?.get(value.to_key())? {
+            Some(id) => self.find(db::key_id(&id)?),
+            None => Ok(None),
+        }
+    }
+
+    /// Find all `Document`s with the given value for a sled index field, ordered by `id`. Does
+    /// not use the search index.
+    ///
+    /// `field_name` is the name of a field marked `sled_index` (see
+    /// `db::TreeBuilder::with_sled_index`).
+    pub fn find_by<K>(
+        &self,
+        field_name: &str,
+        value: &K,
+    ) -> err::Result<impl DoubleEndedIterator<Item = err::Result<Document<T>>> + '_>
+    where
+        K: db::IndexKey + ?Sized,
+    {
+        let entries = self.tree.index_tree(field_name)?.scan_prefix(value.to_key());
+
+        Ok(self.index_entries_to_docs(entries))
+    }
+
+    /// Find all `Document`s with values for a sled index field within `range`, ordered by value
+    /// then `id`. Does not use the search index.
+    ///
+    /// A `Document` with several values in the range (e.g. a `Vec` field) is returned once per
+    /// value.
+    pub fn range_by<K, R>(
+        &self,
+        field_name: &str,
+        range: R,
+    ) -> err::Result<impl DoubleEndedIterator<Item = err::Result<Document<T>>> + '_>
+    where
+        K: db::IndexKey + ?Sized,
+        R: std::ops::RangeBounds<K>,
+    {
+        use std::ops::Bound;
+
+        // Entries are the encoded value followed by the `id`, so bounds are extended to cover
+        // every `id` for the bounding values.
+        let with_max_id = |value: &K| {
+            let mut key = value.to_key();
+            key.extend_from_slice(&db::id_key(u64::MAX));
+            key
+        };
+
+        let start = match range.start_bound() {
+            Bound::Included(value) => Bound::Included(value.to_key()),
+            Bound::Excluded(value) => Bound::Excluded(with_max_id(value)),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        let end = match range.end_bound() {
+            Bound::Included(value) => Bound::Included(with_max_id(value)),
+            Bound::Excluded(value) => Bound::Excluded(value.to_key()),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        let entries = self.tree.index_tree(field_name)?.range((start, end));
+
+        Ok(self.index_entries_to_docs(entries))
     }
 
     /// Delete all `Document`s
@@ -444,35 +762,564 @@ impl<T: DocumentLike> Store<T> {
             .map(|x| x.map_err(err::Error::from))
             .collect::<err::Result<Vec<_>>>()?;
 
-        self.tree.transaction(
-            |tree| -> sled::transaction::ConflictableTransactionResult<_, err::Error> {
-                for key in &keys {
-                    tree.remove(key)?;
-                }
-                self.index.with_writer(|index_writer| {
-                    index_writer
-                        .delete_all_documents()
-                        .map_err(err::Error::Tantivy)
-                        .map_err(sled::transaction::ConflictableTransactionError::Abort)?;
-
-                    index_writer
-                        .commit()
-                        .map_err(err::Error::Tantivy)
-                        .map_err(sled::transaction::ConflictableTransactionError::Abort)?;
-
-                    Ok(())
-                })
-            },
+        let pending = self.tree.transaction(|txn| {
+            keys.iter()
+                .map(|key| self.txn_delete(txn, db::key_id(key)?))
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+
+        self.apply_index_changes(vec![IndexChange::Reindex], &pending)
+    }
+
+    /// Watch for changes to `Document`s, see `db::Watch`.
+    pub fn watch(&self) -> db::Watch<T> {
+        self.tree.watchers.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+
+        db::Watch {
+            subscriber: self.tree.changes.watch_prefix(vec![]),
+            watchers: self.tree.watchers.clone(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Compare the `id`s in the tree with the `id`s in the search index.
+    pub fn verify(&self) -> err::Result<Verification> {
+        use std::collections::{BTreeMap, BTreeSet};
+
+        let tree_ids = self
+            .tree
+            .iter()
+            .keys()
+            .map(|key| db::key_id(&key?))
+            .collect::<err::Result<BTreeSet<_>>>()?;
+
+        let mut index_id_counts = BTreeMap::new();
+
+        for id in self.index.all_ids()? {
+            *index_id_counts.entry(id).or_insert(0usize) += 1;
+        }
+
+        Ok(Verification {
+            orphaned: index_id_counts.keys().filter(|id| !tree_ids.contains(id)).copied().collect(),
+            missing: tree_ids
+                .iter()
+                .filter(|id| !index_id_counts.contains_key(id))
+                .copied()
+                .collect(),
+            duplicated: index_id_counts
+                .iter()
+                .filter(|(id, count)| **count > 1 && tree_ids.contains(id))
+                .map(|(id, _)| *id)
+                .collect(),
+        })
+    }
+
+    /// Fix any discrepancies found by `verify`, without re-indexing the whole datastore.
+    ///
+    /// Returns the discrepancies that were repaired.
+    pub fn repair(&self) -> err::Result<Verification> {
+        let verification = self.verify()?;
+
+        if verification.is_ok() {
+            return Ok(verification);
+        }
+
+        let changes = verification
+            .orphaned
+            .iter()
+            .chain(&verification.missing)
+            .chain(&verification.duplicated)
+            .map(|id| IndexChange::Refresh(*id))
+            .collect();
+
+        self.apply_index_changes(changes, &[])?;
+
+        self.commit()?;
+
+        Ok(verification)
+    }
+
+    /// Get the secondary keys for a replaced or removed value, if there are any secondary trees.
+    fn old_secondary_keys(&self, old: Option<sled::IVec>) -> err::Result<db::SecondaryKeys> {
+        match old {
+            Some(bytes) if self.tree.has_secondary() => {
+                Ok(secondary_keys(&crate::serialize::deserialize::<T>(&bytes)?))
+            }
+            _ => Ok(db::SecondaryKeys::default()),
+        }
+    }
+
+    /// Build the secondary trees from existing `Document`s, if any have not been built yet.
+    fn build_secondary(&self) -> err::Result<()> {
+        if self.tree.secondary_built()? {
+            return Ok(());
+        }
+
+        self.tree.clear_secondary()?;
+
+        for doc in self.iter() {
+            let Document { id, inner, .. } = doc?;
+            let keys = secondary_keys(&inner);
+            self.tree.transaction(|txn| {
+                self.tree.update_secondary(txn, id, &db::SecondaryKeys::default(), &keys)
+            })?;
+        }
+
+        self.tree.mark_secondary_built()
+    }
+
+    /// Load the `Document`s for sled index entries, skipping any removed since the entries were read.
+    fn index_entries_to_docs(
+        &self,
+        entries: sled::Iter,
+    ) -> impl DoubleEndedIterator<Item = err::Result<Document<T>>> + '_ {
+        entries
+            .keys()
+            .map(move |entry| self.find(db::index_entry_id(&entry?)?))
+            .filter_map(Result::transpose)
+    }
+
+    /// Write new `(id, inner)` values, generating `id`s where not supplied, returns the `id`s.
+    fn write_creates(&self, creates: &[(Option<u64>, &T)]) -> err::Result<Vec<u64>> {
+        let (ids, (changes, pending)) = self.tree_creates(creates)?;
+        self.apply_index_changes(changes, &pending)?;
+        Ok(ids)
+    }
+
+    /// Write updated `(id, expected version, inner)` values, returns the new versions.
+    fn write_updates(&self, updates: &[(u64, Option<u64>, &T)]) -> err::Result<Vec<u64>> {
+        let (new_versions, (changes, pending)) = self.tree_updates(updates)?;
+        self.apply_index_changes(changes, &pending)?;
+        Ok(new_versions)
+    }
+
+    /// Write new `(id, inner)` values to the tree only, returns the `id`s and the changes to apply
+    /// to the index.
+    ///
+    /// Fails without writing anything if the transaction aborts.
+    fn tree_creates(
+        &self,
+        creates: &[(Option<u64>, &T)],
+    ) -> err::Result<(Vec<u64>, transaction::Changes)> {
+        let prepared = creates
+            .iter()
+            .map(|(_, inner)| self.prepare(inner))
+            .collect::<err::Result<Vec<_>>>()?;
+
+        let (ids, pending): (Vec<_>, Vec<_>) = self
+            .tree
+            .transaction(|txn| {
+                creates
+                    .iter()
+                    .zip(&prepared)
+                    .map(|((id, _), prepared)| self.txn_create(txn, *id, prepared))
+                    .collect::<Result<Vec<_>, _>>()
+            })?
+            .into_iter()
+            .unzip();
+
+        let changes = ids
+            .iter()
+            .zip(prepared)
+            .map(|(id, prepared)| IndexChange::Upsert(*id, prepared))
+            .collect();
+
+        Ok((ids, (changes, pending)))
+    }
+
+    /// Write updated `(id, expected version, inner)` values to the tree only, returns the new
+    /// versions and the changes to apply to the index.
+    ///
+    /// Fails without writing anything if the transaction aborts.
+    fn tree_updates(
+        &self,
+        updates: &[(u64, Option<u64>, &T)],
+    ) -> err::Result<(Vec<u64>, transaction::Changes)> {
+        let prepared = updates
+            .iter()
+            .map(|(_, _, inner)| self.prepare(inner))
+            .collect::<err::Result<Vec<_>>>()?;
+
+        let (new_versions, pending): (Vec<_>, Vec<_>) = self
+            .tree
+            .transaction(|txn| {
+                updates
+                    .iter()
+                    .zip(&prepared)
+                    .map(|((id, expected, _), prepared)| {
+                        self.txn_update(txn, *id, *expected, prepared)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })?
+            .into_iter()
+            .unzip();
+
+        let changes = updates
+            .iter()
+            .zip(prepared)
+            .map(|((id, _, _), prepared)| IndexChange::Upsert(*id, prepared))
+            .collect();
+
+        Ok((new_versions, (changes, pending)))
+    }
+
+    /// Delete `Document`s from the tree only, returns the changes to apply to the index.
+    ///
+    /// Fails without deleting anything if the transaction aborts.
+    fn tree_deletes(&self, ids: &[u64]) -> err::Result<transaction::Changes> {
+        let pending = self.tree.transaction(|txn| {
+            ids.iter().map(|id| self.txn_delete(txn, *id)).collect::<Result<Vec<_>, _>>()
+        })?;
+
+        let changes = ids.iter().map(|id| IndexChange::Refresh(*id)).collect();
+
+        Ok((changes, pending))
+    }
+
+    /// Serialize and convert a `Document` ahead of writing it.
+    fn prepare(&self, inner: &T) -> err::Result<PreparedDocument> {
+        Ok(PreparedDocument {
+            serialized: crate::serialize::serialize(inner)?,
+            search_doc: inner.as_index_document(&self.index.fields)?,
+            secondary_keys: secondary_keys(inner),
+        })
+    }
+
+    /// Create a `Document` within a transaction, returns the `id` and pending log entry.
+    fn txn_create(
+        &self,
+        txn: &db::Transaction<'_>,
+        id: Option<u64>,
+        prepared: &PreparedDocument,
+    ) -> sled::transaction::ConflictableTransactionResult<(u64, (u64, u64)), err::Error> {
+        let id = match id {
+            Some(id) => {
+                self.tree.reserve_id(txn, id)?;
+                id
+            }
+            None => self.tree.generate_id(txn)?,
+        };
+
+        if txn.tree.insert(&db::id_key(id), prepared.serialized.as_slice())?.is_some() {
+            return Err(err::Error::AlreadyExists { id }.into());
+        }
+
+        txn.next_version(id, false, None)?;
+
+        self.tree.update_secondary(
+            txn,
+            id,
+            &db::SecondaryKeys::default(),
+            &prepared.secondary_keys,
         )?;
 
+        let entry = txn.log_pending(id)?;
+
+        txn.record_change(entry, Some(0), None, Some(&prepared.serialized))?;
+
+        Ok((id, entry))
+    }
+
+    /// Create or replace a `Document` within a transaction, returns the new version and pending
+    /// log entry.
+    fn txn_update(
+        &self,
+        txn: &db::Transaction<'_>,
+        id: u64,
+        expected: Option<u64>,
+        prepared: &PreparedDocument,
+    ) -> sled::transaction::ConflictableTransactionResult<(u64, (u64, u64)), err::Error> {
+        self.tree.reserve_id(txn, id)?;
+
+        let old = txn.tree.insert(&db::id_key(id), prepared.serialized.as_slice())?;
+
+        let version = txn.next_version(id, old.is_some(), expected)?;
+
+        let entry = txn.log_pending(id)?;
+
+        txn.record_change(entry, Some(version), old.as_deref(), Some(&prepared.serialized))?;
+
+        let old_keys = self.old_secondary_keys(old)?;
+        self.tree.update_secondary(txn, id, &old_keys, &prepared.secondary_keys)?;
+
+        Ok((version, entry))
+    }
+
+    /// Update a `Document` in place within a transaction, returns the updated `Document`, its
+    /// prepared form and pending log entry.
+    #[allow(clippy::type_complexity)]
+    fn txn_update_with(
+        &self,
+        txn: &db::Transaction<'_>,
+        id: u64,
+        f: &mut dyn FnMut(&mut T),
+    ) -> sled::transaction::ConflictableTransactionResult<
+        Option<(Document<T>, PreparedDocument, (u64, u64))>,
+        err::Error,
+    > {
+        let old = match txn.tree.get(db::id_key(id))? {
+            Some(old) => old,
+            None => return Ok(None),
+        };
+
+        let mut inner: T = crate::serialize::deserialize(&old)?;
+
+        f(&mut inner);
+
+        let prepared = self.prepare(&inner)?;
+
+        txn.tree.insert(&db::id_key(id), prepared.serialized.as_slice())?;
+
+        let version = txn.next_version(id, true, None)?;
+
+        let entry = txn.log_pending(id)?;
+
+        txn.record_change(entry, Some(version), Some(&old), Some(&prepared.serialized))?;
+
+        let old_keys = self.old_secondary_keys(Some(old))?;
+        self.tree.update_secondary(txn, id, &old_keys, &prepared.secondary_keys)?;
+
+        Ok(Some((Document { id, inner, version: Some(version) }, prepared, entry)))
+    }
+
+    /// Delete a `Document` within a transaction, returns the pending log entry.
+    fn txn_delete(
+        &self,
+        txn: &db::Transaction<'_>,
+        id: u64,
+    ) -> sled::transaction::ConflictableTransactionResult<(u64, u64), err::Error> {
+        // The version is left in place, see `db::Transaction::next_version`.
+        let old = txn.tree.remove(&db::id_key(id))?;
+
+        let entry = txn.log_pending(id)?;
+
+        txn.record_change(entry, None, old.as_deref(), None)?;
+
+        let old_keys = self.old_secondary_keys(old)?;
+        self.tree.update_secondary(txn, id, &old_keys, &db::SecondaryKeys::default())?;
+
+        Ok(entry)
+    }
+
+    /// Apply changes to the search index, after the matching tree changes have been logged.
+    ///
+    /// Changes are committed according to the `CommitPolicy`.
+    fn apply_index_changes(
+        &self,
+        changes: Vec<IndexChange>,
+        pending: &[(u64, u64)],
+    ) -> err::Result<()> {
+        self.tree.clear_changes(pending)?;
+
+        self.with_commit_state(|index_writer, commit_state| {
+            self.replay_rolled_back(index_writer, commit_state)?;
+
+            commit_state.uncommitted += changes.len();
+            commit_state.pending.extend_from_slice(pending);
+
+            for change in changes {
+                match change {
+                    IndexChange::Upsert(id, prepared) => {
+                        self.index_stored(index_writer, id, Some(prepared))?
+                    }
+                    IndexChange::Refresh(id) => self.index_stored(index_writer, id, None)?,
+                    IndexChange::Reindex => self.reindex_with_writer(index_writer)?,
+                }
+            }
+
+            if self.commit_policy.should_commit(commit_state) {
+                self.commit_with_writer(index_writer, commit_state)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Run `f` with the index writer and the `CommitState`.
+    ///
+    /// If `f` fails, `search::Index::with_writer` rolls back the uncommitted changes, so their
+    /// logged entries are left in place and marked to be replayed, rather than marked as applied
+    /// by the next commit.
+    fn with_commit_state<F, R>(&self, f: F) -> err::Result<R>
+    where
+        F: FnOnce(&mut tantivy::IndexWriter, &mut CommitState) -> err::Result<R>,
+    {
+        self.index.with_writer(|index_writer| {
+            let mut commit_state = self.commit_state.lock().map_err(err::custom)?;
+
+            let out = f(index_writer, &mut commit_state);
+
+            if out.is_err() {
+                *commit_state = CommitState { replay: true, ..CommitState::default() };
+            }
+
+            out
+        })
+    }
+
+    /// Replay the logged entries with the next index change or commit, after changes failed to
+    /// apply.
+    fn mark_for_replay(&self) {
+        if let Ok(mut commit_state) = self.commit_state.lock() {
+            commit_state.replay = true;
+        }
+    }
+
+    /// Apply the logged entries again if uncommitted changes were rolled back after an error.
+    fn replay_rolled_back(
+        &self,
+        index_writer: &mut tantivy::IndexWriter,
+        commit_state: &mut CommitState,
+    ) -> err::Result<()> {
+        if !commit_state.replay {
+            return Ok(());
+        }
+
+        let pending = self.tree.pending_entries()?;
+
+        for (id, _) in &pending {
+            self.index_stored(index_writer, *id, None)?;
+        }
+
+        commit_state.uncommitted += pending.len();
+        commit_state.pending.extend(pending);
+        commit_state.replay = false;
+
         Ok(())
     }
+
+    /// Replace the index entry for `id` with its stored value, or remove it if there is none.
+    ///
+    /// `written` is indexed as-is if it is still the stored value, otherwise the stored value has
+    /// been replaced by a later write and is converted again.
+    fn index_stored(
+        &self,
+        index_writer: &mut tantivy::IndexWriter,
+        id: u64,
+        written: Option<PreparedDocument>,
+    ) -> err::Result<()> {
+        index_writer.delete_term(tantivy::Term::from_field_u64(self.index.id_field, id));
+
+        let stored = match self.tree.get(db::id_key(id))? {
+            Some(stored) => stored,
+            None => return Ok(()),
+        };
+
+        let mut search_doc = match written {
+            Some(written) if stored == written.serialized.as_slice() => written.search_doc,
+            _ => crate::serialize::deserialize::<T>(&stored)?
+                .as_index_document(&self.index.fields)?,
+        };
+
+        search_doc.add_u64(self.index.id_field, id);
+
+        index_writer.add_document(search_doc);
+
+        Ok(())
+    }
+
+    /// Replace everything in the search index with the stored `Document`s.
+    ///
+    /// Uncommitted changes are rolled back first, as `delete_all_documents` only removes
+    /// committed ones; they are in the tree, so are indexed again.
+    fn reindex_with_writer(&self, index_writer: &mut tantivy::IndexWriter) -> err::Result<()> {
+        index_writer.rollback()?;
+        index_writer.delete_all_documents()?;
+
+        for doc in self.iter() {
+            let Document { id, inner, .. } = doc?;
+
+            let mut search_doc = inner.as_index_document(&self.index.fields)?;
+
+            search_doc.add_u64(self.index.id_field, id);
+
+            index_writer.add_document(search_doc);
+        }
+
+        Ok(())
+    }
+
+    /// Commit the index writer and mark the logged entries as applied.
+    ///
+    /// The tree is flushed first, so that if the process dies before the index commit, the logged
+    /// entries are available to be replayed by `recover`.
+    fn commit_with_writer(
+        &self,
+        index_writer: &mut tantivy::IndexWriter,
+        commit_state: &mut CommitState,
+    ) -> err::Result<()> {
+        self.tree.flush()?;
+
+        let opstamp = index_writer.commit()?;
+
+        self.index.reader.reload()?;
+
+        self.tree.mark_applied(&commit_state.pending, opstamp)?;
+
+        *commit_state = CommitState::default();
+
+        Ok(())
+    }
+
+    /// Bring the search index back in sync with the tree after an unclean shutdown.
+    ///
+    /// Replays any logged changes that were not committed to the index, or re-indexes everything
+    /// if the index is older than the last recorded commit (e.g. if it was deleted or restored).
+    fn recover(&self) -> err::Result<()> {
+        let index_opstamp = self.index.inner.load_metas()?.opstamp;
+
+        if self.tree.opstamp()?.map(|opstamp| opstamp > index_opstamp).unwrap_or(false) {
+            return self.index_all();
+        }
+
+        let pending = self.tree.pending_entries()?;
+
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let changes = pending.iter().map(|(id, _)| IndexChange::Refresh(*id)).collect();
+
+        self.apply_index_changes(changes, &pending)?;
+
+        self.commit()
+    }
+}
+
+impl<T: DocumentLike> Drop for Store<T> {
+    // Errors are ignored, as the logged entries for changes that failed to commit are replayed
+    // by `StoreBuilder::finish`.
+    fn drop(&mut self) {
+        if self.commit_policy != CommitPolicy::Manual {
+            let _ = self.commit();
+        }
+    }
+}
+
+/// Discrepancies between the tree and the search index, as found by `Store::verify`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Verification {
+    /// Index entries with no matching `Document` in the tree.
+    pub orphaned: Vec<u64>,
+    /// `Document`s with no index entry.
+    pub missing: Vec<u64>,
+    /// `Document`s with more than one index entry.
+    pub duplicated: Vec<u64>,
+}
+
+impl Verification {
+    /// Returns `true` if no discrepancies were found.
+    pub fn is_ok(&self) -> bool {
+        self.orphaned.is_empty() && self.missing.is_empty() && self.duplicated.is_empty()
+    }
 }
 
 /// Builder for `Store`
 pub struct StoreBuilder<T: DocumentLike> {
     tree_builder: db::TreeBuilder,
     index_builder: search::IndexBuilder<T::IndexFieldsType>,
+    commit_policy: CommitPolicy,
+    schema_mismatch_policy: SchemaMismatchPolicy,
     marker: PhantomData<fn(T)>,
 }
 
@@ -481,6 +1328,8 @@ impl<T: DocumentLike> Default for StoreBuilder<T> {
         StoreBuilder {
             tree_builder: db::TreeBuilder::default(),
             index_builder: search::IndexBuilder::default(),
+            commit_policy: CommitPolicy::default(),
+            schema_mismatch_policy: SchemaMismatchPolicy::default(),
             marker: PhantomData,
         }
     }
@@ -514,23 +1363,85 @@ impl<T: DocumentLike> StoreBuilder<T> {
         self
     }
 
+    /// Shortcut method to add a migration of stored `Document`s to the `tree_builder`, see
+    /// `db::Migrations`.
+    pub fn with_migration<A, B, F>(mut self, from: u64, f: F) -> Self
+    where
+        A: serde::de::DeserializeOwned,
+        B: serde::Serialize,
+        F: Fn(A) -> B + 'static,
+    {
+        self.tree_builder = self.tree_builder.with_migration(from, f);
+        self
+    }
+
+    /// Set when changes are committed to the search index.
+    ///
+    /// By default will use `CommitPolicy::EveryWrite`.
+    pub fn with_commit_policy(mut self, commit_policy: CommitPolicy) -> Self {
+        self.commit_policy = commit_policy;
+        self
+    }
+
+    /// Set what to do if the existing search index has a different schema.
+    ///
+    /// By default will use `SchemaMismatchPolicy::Fail`.
+    pub fn with_schema_mismatch_policy(
+        mut self,
+        schema_mismatch_policy: SchemaMismatchPolicy,
+    ) -> Self {
+        self.schema_mismatch_policy = schema_mismatch_policy;
+        self
+    }
+
     /// Convert into finished `Store`
+    ///
+    /// Runs any outstanding migrations, and rebuilds the search index if it was recreated (see
+    /// `SchemaMismatchPolicy`) or a migration has run since it was last built.
     pub fn finish(self) -> err::Result<Store<T>> {
         let tree = self.tree_builder.merge(T::tree_builder()).finish()?;
 
-        let index = self.index_builder.merge(T::index_builder()).finish()?;
+        let recreate = self.schema_mismatch_policy == SchemaMismatchPolicy::Recreate
+            || tree.reindex_requested()?;
+
+        let (index, recreated) =
+            self.index_builder.merge(T::index_builder()).finish_recreating(recreate)?;
 
-        Ok(Store { tree, index, marker: PhantomData })
+        let store = Store {
+            tree,
+            index,
+            marker: PhantomData,
+            commit_policy: self.commit_policy,
+            commit_state: Mutex::new(CommitState::default()),
+        };
+
+        store.build_secondary()?;
+
+        if recreated || store.tree.reindex_requested()? {
+            // Requested until rebuilt, in case rebuilding is interrupted.
+            store.tree.request_reindex()?;
+            store.index_all()?;
+            store.tree.mark_reindexed()?;
+        } else {
+            store.recover()?;
+        }
+
+        Ok(store)
     }
 }
 
+fn secondary_keys<T: DocumentLike>(inner: &T) -> db::SecondaryKeys {
+    db::SecondaryKeys { unique: inner.unique_keys(), indexed: inner.sled_index_keys() }
+}
+
 /// Defines methods for building the index schema and creating a `tantivy::Document`.
 ///
 /// `pallet_macros` provides a way to automatically derive this trait.
 pub trait DocumentLike: serde::Serialize + serde::de::DeserializeOwned {
     /// The container for an index's fields.
     ///
-    /// When using `pallet_macros`, this is a wrapped `Vec<tantivy::schema::Field>`.
+    /// When using `pallet_macros`, this is a generated struct with a `tantivy::schema::Field` for
+    /// each indexed field, e.g. `BookFields` for `Book`.
     type IndexFieldsType;
 
     /// Given the specified document and fields container, returns a `tantivy::Document`.
@@ -546,6 +1457,22 @@ pub trait DocumentLike: serde::Serialize + serde::de::DeserializeOwned {
         db::TreeBuilder::default()
     }
 
+    /// Returns the encoded keys for each unique field, by field name.
+    ///
+    /// Each field must also be added with `db::TreeBuilder::with_unique_field`. When using
+    /// `pallet_macros`, this is generated for fields with the `unique` attribute.
+    fn unique_keys(&self) -> Vec<(&'static str, Vec<Vec<u8>>)> {
+        Vec::new()
+    }
+
+    /// Returns the encoded keys for each sled index field, by field name.
+    ///
+    /// Each field must also be added with `db::TreeBuilder::with_sled_index`. When using
+    /// `pallet_macros`, this is generated for fields with the `sled_index` attribute.
+    fn sled_index_keys(&self) -> Vec<(&'static str, Vec<Vec<u8>>)> {
+        Vec::new()
+    }
+
     /// Can be provided to set some or all of the `Index` config.
     ///
     /// Will be merged with any configuration provided in `StoreBuilder::index_builder`
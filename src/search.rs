@@ -2,14 +2,39 @@ use crate::{err, Document, DocumentLike, Store};
 use std::path::PathBuf;
 use std::sync::Mutex;
 
+mod aggregations;
 mod as_query;
+mod faceted;
 mod field_value;
+mod fuzzy;
+mod highlighted;
+mod paged;
 mod params;
+mod query;
+mod schema_diff;
 mod scored_ids;
-
+mod sorted;
+mod suggest;
+mod typed_field;
+
+pub use aggregations::{
+    Aggregated, AggregatedResults, Aggregation, AggregationResult, Aggregations, DateHistogram,
+    DateHistogramBucket, DateInterval, FieldStats, Histogram, HistogramBucket, Stats, TermKey,
+    Terms, TermsBucket,
+};
 pub use as_query::AsQuery;
+pub use faceted::{FacetCount, Faceted, FacetedResults};
+pub use field_value::Facet;
+pub use fuzzy::Fuzzy;
+pub use highlighted::{Highlighted, HighlightedHit, HighlightedResults, Snippet};
+pub use paged::{Page, Paged};
 pub use params::Params;
+pub use query::{Query, QueryValue};
+pub use schema_diff::SchemaDifference;
 pub use scored_ids::{ScoredId, ScoredIds};
+pub use sorted::{Order, Sorted, SortedIds};
+pub use suggest::Suggestion;
+pub use typed_field::TypedField;
 
 // For use primarily by `pallet_macros`.
 #[doc(hidden)]
@@ -20,11 +45,14 @@ pub use field_value::FieldValue;
 pub struct FieldsContainer(pub Vec<tantivy::schema::Field>);
 
 /// Wrapper around `tantivy::Index`, with additional search data
+#[allow(clippy::type_complexity)]
 pub struct Index<T> {
     pub id_field: tantivy::schema::Field,
     pub fields: T,
     default_search_fields: Vec<tantivy::schema::Field>,
-    inner: tantivy::Index,
+    suggest_fields: Vec<tantivy::schema::Field>,
+    pub(crate) inner: tantivy::Index,
+    pub(crate) reader: tantivy::IndexReader,
     pub(crate) writer: Mutex<Option<tantivy::IndexWriter>>,
     writer_accessor:
         Box<dyn Fn(&tantivy::Index) -> tantivy::Result<tantivy::IndexWriter> + Send + Sync>,
@@ -35,14 +63,58 @@ impl<T> Index<T> {
     pub fn builder() -> IndexBuilder<T> {
         IndexBuilder::default()
     }
+    /// Get the long-lived `tantivy::IndexReader`, reloaded after every commit made by the `Store`.
+    pub fn reader(&self) -> &tantivy::IndexReader {
+        &self.reader
+    }
+
     /// Get the query parser associated with index and default search fields.
     pub fn query_parser(&self) -> tantivy::query::QueryParser {
         tantivy::query::QueryParser::for_index(&self.inner, self.default_search_fields.clone())
     }
 
+    /// Get a field from the index schema by name.
+    pub fn field(&self, field_name: &str) -> err::Result<tantivy::schema::Field> {
+        self.inner
+            .schema()
+            .get_field(field_name)
+            .ok_or_else(|| err::custom(format!("Unknown field `{}`", field_name)))
+    }
+
+    /// Get the datastore `id` of every document in the index, including any duplicates.
+    pub(crate) fn all_ids(&self) -> err::Result<Vec<u64>> {
+        let scored_ids = self.reader.searcher().search(
+            &tantivy::query::AllQuery,
+            &ScoredIds { size_hint: None, id_field: self.id_field },
+        )?;
+
+        Ok(scored_ids.into_iter().map(|ScoredId { id, .. }| id).collect())
+    }
+
+    /// Complete the last term of `prefix` from the terms of a suggest field, see `Store::suggest`.
+    pub(crate) fn suggest(
+        &self,
+        prefix: &str,
+        field_name: &str,
+        limit: usize,
+    ) -> err::Result<Vec<Suggestion>> {
+        let field = self.field(field_name)?;
+
+        if !self.suggest_fields.contains(&field) {
+            return Err(err::custom(format!("Unknown suggest field `{}`", field_name)));
+        }
+
+        suggest::suggest(&self.inner, &self.reader, field, prefix, limit)
+    }
+
+    /// Run `cls` with the index writer, creating it if needed.
+    ///
+    /// If `cls` fails, any uncommitted changes are rolled back, so that a partly applied batch is
+    /// never committed. If the rollback also fails, the writer is dropped and created again on
+    /// next use.
     pub(crate) fn with_writer<F, S, E>(&self, cls: F) -> Result<S, E>
     where
-        F: Fn(&mut tantivy::IndexWriter) -> Result<S, E>,
+        F: FnOnce(&mut tantivy::IndexWriter) -> Result<S, E>,
         E: From<err::Error>,
     {
         let mut lock = self.writer.lock().map_err(err::custom).map_err(E::from)?;
@@ -54,23 +126,28 @@ impl<T> Index<T> {
             }
         };
 
-        let out = cls(&mut writer)?;
+        let out = cls(&mut writer);
 
-        *lock = Some(writer);
+        if out.is_ok() || writer.rollback().is_ok() {
+            *lock = Some(writer);
+        }
 
-        Ok(out)
+        out
     }
 }
 
 /// Builder for an `Index`
+#[allow(clippy::type_complexity)]
 pub struct IndexBuilder<T> {
     fields_builder: Option<Box<dyn Fn(&mut tantivy::schema::SchemaBuilder) -> err::Result<T>>>,
     default_search_fields_builder: Option<Box<dyn Fn(&T) -> Vec<tantivy::schema::Field>>>,
+    suggest_fields: Vec<String>,
     writer_accessor:
         Option<Box<dyn Fn(&tantivy::Index) -> tantivy::Result<tantivy::IndexWriter> + Send + Sync>>,
     index_dir: Option<PathBuf>,
     config: Option<Box<dyn Fn(&mut tantivy::Index) -> tantivy::Result<()>>>,
     id_field_name: Option<String>,
+    reload_policy: Option<tantivy::ReloadPolicy>,
 }
 
 impl<T> Default for IndexBuilder<T> {
@@ -78,10 +155,12 @@ impl<T> Default for IndexBuilder<T> {
         IndexBuilder {
             fields_builder: None,
             default_search_fields_builder: None,
+            suggest_fields: Vec::new(),
             writer_accessor: None,
             index_dir: None,
             config: None,
             id_field_name: None,
+            reload_policy: None,
         }
     }
 }
@@ -95,6 +174,8 @@ impl<T> IndexBuilder<T> {
             index_dir: a4,
             config: a5,
             id_field_name: a6,
+            reload_policy: a7,
+            suggest_fields: mut a8,
         } = self;
 
         let IndexBuilder {
@@ -104,8 +185,12 @@ impl<T> IndexBuilder<T> {
             index_dir: b4,
             config: b5,
             id_field_name: b6,
+            reload_policy: b7,
+            suggest_fields: b8,
         } = other;
 
+        a8.extend(b8.into_iter().filter(|x| !a8.contains(x)).collect::<Vec<_>>());
+
         IndexBuilder {
             fields_builder: a1.or(b1),
             default_search_fields_builder: a2.or(b2),
@@ -113,6 +198,8 @@ impl<T> IndexBuilder<T> {
             index_dir: a4.or(b4),
             config: a5.or(b5),
             id_field_name: a6.or(b6),
+            reload_policy: a7.or(b7),
+            suggest_fields: a8,
         }
     }
 
@@ -152,6 +239,16 @@ impl<T> IndexBuilder<T> {
         self
     }
 
+    /// Set the reload policy for the `tantivy::IndexReader`.
+    ///
+    /// By default will use `tantivy::ReloadPolicy::Manual`; the reader is always reloaded after
+    /// commits made by the `Store`, so other policies are only needed if the index is also written
+    /// to elsewhere.
+    pub fn with_reload_policy(mut self, reload_policy: tantivy::ReloadPolicy) -> Self {
+        self.reload_policy = Some(reload_policy);
+        self
+    }
+
     /// Handler that adds fields to a schema, and returns them in the fields container
     pub fn with_fields_builder<F>(mut self, fields_builder: F) -> Self
     where
@@ -170,8 +267,23 @@ impl<T> IndexBuilder<T> {
         self
     }
 
+    /// Add a text field whose terms are completed by `Store::suggest`.
+    pub fn with_suggest_field<I: Into<String>>(mut self, field_name: I) -> Self {
+        self.suggest_fields.push(field_name.into());
+        self
+    }
+
     /// Convert into finished `Index`
+    ///
+    /// Fails with `err::Error::SchemaMismatch` if an index exists in the directory with a
+    /// different schema.
     pub fn finish(self) -> err::Result<Index<T>> {
+        self.finish_recreating(false).map(|(index, _)| index)
+    }
+
+    /// Convert into finished `Index`, also returns `true` if an existing index with a different
+    /// schema was recreated (empty), instead of failing with `err::Error::SchemaMismatch`.
+    pub(crate) fn finish_recreating(self, recreate: bool) -> err::Result<(Index<T>, bool)> {
         let fields_builder =
             self.fields_builder.ok_or_else(|| err::custom("`fields_builder` not set"))?;
 
@@ -190,10 +302,41 @@ impl<T> IndexBuilder<T> {
 
         let schema = schema_builder.build();
 
+        let suggest_fields = self
+            .suggest_fields
+            .iter()
+            .map(|field_name| {
+                let field = schema
+                    .get_field(field_name)
+                    .ok_or_else(|| err::custom(format!("Unknown field `{}`", field_name)))?;
+                suggest::check_suggest_field(&schema, field)?;
+                Ok(field)
+            })
+            .collect::<err::Result<Vec<_>>>()?;
+
         let mmap_dir = tantivy::directory::MmapDirectory::open(&index_dir)
             .map_err(tantivy::TantivyError::from)?;
 
-        let mut index = tantivy::Index::open_or_create(mmap_dir, schema)?;
+        let mut recreated = false;
+
+        let exists = tantivy::Index::exists(&mmap_dir).map_err(tantivy::TantivyError::from)?;
+
+        let mut index = if exists {
+            let index = tantivy::Index::open(mmap_dir.clone())?;
+
+            let differences = schema_diff::schema_differences(&index.schema(), &schema);
+
+            if differences.is_empty() {
+                index
+            } else if recreate {
+                recreated = true;
+                tantivy::Index::create(mmap_dir, schema)?
+            } else {
+                return Err(err::Error::SchemaMismatch { differences });
+            }
+        } else {
+            tantivy::Index::create(mmap_dir, schema)?
+        };
 
         if let Some(config) = self.config {
             config(&mut index)?;
@@ -211,16 +354,23 @@ impl<T> IndexBuilder<T> {
                 Vec::new()
             };
 
-        // let writer = writer_accessor(&index)?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(self.reload_policy.unwrap_or(tantivy::ReloadPolicy::Manual))
+            .try_into()?;
 
-        Ok(Index {
+        let index = Index {
             default_search_fields,
+            suggest_fields,
             inner: index,
+            reader,
             id_field,
             fields,
             writer_accessor,
             writer: Mutex::new(None),
-        })
+        };
+
+        Ok((index, recreated))
     }
 }
 
@@ -264,9 +414,7 @@ where
             ..
         } = self;
 
-        let reader = store.index.inner.reader().map_err(err::Error::from)?;
-
-        let searcher = reader.searcher();
+        let searcher = store.index.reader.searcher();
 
         let query = query_like.as_query(&store.index.inner, &store.index.default_search_fields)?;
 
@@ -286,8 +434,6 @@ where
     type Error = err::Error;
 
     fn search(&self, store: &Store<T>) -> Result<Self::Item, Self::Error> {
-        use rayon::prelude::*;
-
         let scored_ids_handle = ScoredIds { size_hint: None, id_field: store.index.id_field };
         let count_handle = tantivy::collector::Count;
 
@@ -297,16 +443,26 @@ where
             .with_query(query)
             .with_collector((count_handle, scored_ids_handle))
             .with_handler(|(count, scored_ids)| -> Result<_, err::Error> {
-                let hits = scored_ids
-                    .into_par_iter()
-                    .map(|ScoredId { id, score }| {
-                        store.find(id).map(|opt_doc| opt_doc.map(|doc| Hit { doc, score }))
-                    })
-                    .filter_map(Result::transpose)
-                    .collect::<err::Result<Vec<_>>>()?;
-
+                let hits = load_hits(store, scored_ids)?;
                 Ok(Results { count, hits })
             });
-        Ok(search_params.search(store)?)
+        search_params.search(store)
     }
 }
+
+/// Load the `Document`s for the given `ScoredId`s, preserving order and skipping missing `id`s.
+pub(crate) fn load_hits<T>(store: &Store<T>, scored_ids: Vec<ScoredId>) -> err::Result<Vec<Hit<T>>>
+where
+    T: DocumentLike + Send,
+    T::IndexFieldsType: Sync,
+{
+    use rayon::prelude::*;
+
+    scored_ids
+        .into_par_iter()
+        .map(|ScoredId { id, score }| {
+            store.find(id).map(|opt_doc| opt_doc.map(|doc| Hit { doc, score }))
+        })
+        .filter_map(Result::transpose)
+        .collect()
+}
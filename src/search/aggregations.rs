@@ -0,0 +1,835 @@
+use crate::search::{AsQuery, Page, Params, Results, Searcher};
+use crate::{err, DocumentLike, Store};
+use std::collections::{BTreeMap, HashMap};
+use tantivy::fastfield::{FastFieldReader, FastValue, MultiValuedFastFieldReader};
+use tantivy::schema::{Cardinality, FieldType};
+
+/// Summary of the values of a field, see `Stats`
+///
+/// Date values are given as Unix timestamps in seconds.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FieldStats {
+    pub count: u64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub sum: f64,
+    pub avg: Option<f64>,
+}
+
+/// Number of values in `[key, key + interval)`, see `Histogram`
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct HistogramBucket {
+    pub key: f64,
+    pub count: u64,
+}
+
+/// Number of values in the date interval starting at `key`, see `DateHistogram`
+///
+/// `key` is a Unix timestamp in seconds, and `key_as_string` the same date in RFC 3339 format.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DateHistogramBucket {
+    pub key: i64,
+    pub key_as_string: String,
+    pub count: u64,
+}
+
+/// Field value counted by `Terms`
+///
+/// Dates are given as Unix timestamps in seconds.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum TermKey {
+    U64(u64),
+    I64(i64),
+    F64(f64),
+    Date(i64),
+    Facet(String),
+}
+
+/// Number of values equal to `key`, see `Terms`
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TermsBucket {
+    pub key: TermKey,
+    pub count: u64,
+}
+
+/// Result of any `Aggregation`
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum AggregationResult {
+    Stats(FieldStats),
+    Histogram(Vec<HistogramBucket>),
+    DateHistogram(Vec<DateHistogramBucket>),
+    Terms(Vec<TermsBucket>),
+}
+
+/// Calendar interval of `DateHistogram` buckets, in UTC
+///
+/// Weeks start on Monday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateInterval {
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl DateInterval {
+    // Start of the interval containing `timestamp`.
+    fn truncate(self, timestamp: i64) -> i64 {
+        const DAY: i64 = 24 * 60 * 60;
+        // 1970-01-01 was a Thursday, so weeks are offset by 4 days.
+        const MONDAY: i64 = 4 * DAY;
+
+        match self {
+            DateInterval::Minute => timestamp - timestamp.rem_euclid(60),
+            DateInterval::Hour => timestamp - timestamp.rem_euclid(60 * 60),
+            DateInterval::Day => timestamp - timestamp.rem_euclid(DAY),
+            DateInterval::Week => timestamp - (timestamp - MONDAY).rem_euclid(7 * DAY),
+            DateInterval::Month | DateInterval::Year => {
+                use chrono::Datelike;
+
+                chrono::DateTime::from_timestamp(timestamp, 0)
+                    .and_then(|date| {
+                        let month = if self == DateInterval::Year { 1 } else { date.month() };
+                        chrono::NaiveDate::from_ymd_opt(date.year(), month, 1)
+                    })
+                    .and_then(|date| date.and_hms_opt(0, 0, 0))
+                    .map(|date| date.and_utc().timestamp())
+                    .unwrap_or(timestamp)
+            }
+        }
+    }
+}
+
+/// Collects `FieldStats` for a `u64`, `i64`, `f64` or date field with the `FAST` option
+///
+/// Every value of multi-valued fields is counted.
+#[derive(Debug, Clone)]
+pub struct Stats {
+    pub field_name: String,
+}
+
+impl Stats {
+    /// Create a new `Stats` collector for the given field.
+    pub fn new<I: Into<String>>(field_name: I) -> Self {
+        Stats { field_name: field_name.into() }
+    }
+}
+
+/// Counts the values of a `u64`, `i64`, `f64` or date field with the `FAST` option in buckets
+/// of a fixed width
+///
+/// Buckets are ordered by key, and empty buckets are left out.
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    pub field_name: String,
+    pub interval: f64,
+}
+
+impl Histogram {
+    /// Create a new `Histogram` collector for the given field, with buckets `interval` wide.
+    pub fn new<I: Into<String>>(field_name: I, interval: f64) -> Self {
+        Histogram { field_name: field_name.into(), interval }
+    }
+}
+
+/// Counts the values of a date field with the `FAST` option in calendar intervals
+///
+/// Buckets are ordered by key, and empty buckets are left out.
+#[derive(Debug, Clone)]
+pub struct DateHistogram {
+    pub field_name: String,
+    pub interval: DateInterval,
+}
+
+impl DateHistogram {
+    /// Create a new `DateHistogram` collector for the given field.
+    pub fn new<I: Into<String>>(field_name: I, interval: DateInterval) -> Self {
+        DateHistogram { field_name: field_name.into(), interval }
+    }
+}
+
+/// Counts the most common values of a `u64`, `i64`, `f64` or date field with the `FAST` option,
+/// or of a facet field
+///
+/// Returns at most `size` buckets, ordered by descending count, then by key.
+#[derive(Debug, Clone)]
+pub struct Terms {
+    pub field_name: String,
+    pub size: usize,
+}
+
+impl Terms {
+    /// Create a new `Terms` collector for the given field, returning the `size` most common
+    /// values.
+    pub fn new<I: Into<String>>(field_name: I, size: usize) -> Self {
+        Terms { field_name: field_name.into(), size }
+    }
+}
+
+/// Any of the aggregation collectors, for use with `Aggregations`
+#[derive(Debug, Clone)]
+pub enum Aggregation {
+    Stats(Stats),
+    Histogram(Histogram),
+    DateHistogram(DateHistogram),
+    Terms(Terms),
+}
+
+impl Aggregation {
+    /// The index field name being aggregated.
+    pub fn field_name(&self) -> &str {
+        match self {
+            Aggregation::Stats(stats) => &stats.field_name,
+            Aggregation::Histogram(histogram) => &histogram.field_name,
+            Aggregation::DateHistogram(date_histogram) => &date_histogram.field_name,
+            Aggregation::Terms(terms) => &terms.field_name,
+        }
+    }
+}
+
+impl From<Stats> for Aggregation {
+    fn from(stats: Stats) -> Self {
+        Aggregation::Stats(stats)
+    }
+}
+
+impl From<Histogram> for Aggregation {
+    fn from(histogram: Histogram) -> Self {
+        Aggregation::Histogram(histogram)
+    }
+}
+
+impl From<DateHistogram> for Aggregation {
+    fn from(date_histogram: DateHistogram) -> Self {
+        Aggregation::DateHistogram(date_histogram)
+    }
+}
+
+impl From<Terms> for Aggregation {
+    fn from(terms: Terms) -> Self {
+        Aggregation::Terms(terms)
+    }
+}
+
+/// Runs several named aggregations in a single pass, returning their results by name
+#[derive(Debug, Clone, Default)]
+pub struct Aggregations {
+    pub aggregations: Vec<(String, Aggregation)>,
+}
+
+impl Aggregations {
+    /// Create an empty set of aggregations.
+    pub fn new() -> Self {
+        Aggregations::default()
+    }
+
+    /// Add an aggregation, whose result is returned under `name`.
+    pub fn with_aggregation<I: Into<String>, A: Into<Aggregation>>(
+        mut self,
+        name: I,
+        aggregation: A,
+    ) -> Self {
+        self.aggregations.push((name.into(), aggregation.into()));
+        self
+    }
+}
+
+// Which fast value type the order-preserving `u64`s of a `ValuesReader` represent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum ValueKind {
+    U64,
+    I64,
+    F64,
+    Date,
+}
+
+impl ValueKind {
+    fn to_f64(self, value: u64) -> f64 {
+        match self {
+            ValueKind::U64 => value as f64,
+            ValueKind::I64 | ValueKind::Date => i64::from_u64(value) as f64,
+            ValueKind::F64 => f64::from_u64(value),
+        }
+    }
+
+    fn to_term_key(self, value: u64) -> TermKey {
+        match self {
+            ValueKind::U64 => TermKey::U64(value),
+            ValueKind::I64 => TermKey::I64(i64::from_u64(value)),
+            ValueKind::F64 => TermKey::F64(f64::from_u64(value)),
+            ValueKind::Date => TermKey::Date(i64::from_u64(value)),
+        }
+    }
+}
+
+// Fast field reader for any numeric type or cardinality, values are mapped to order-preserving
+// `u64`s.
+enum ValuesReader {
+    U64(FastFieldReader<u64>),
+    I64(FastFieldReader<i64>),
+    F64(FastFieldReader<f64>),
+    Date(FastFieldReader<tantivy::DateTime>),
+    U64s(MultiValuedFastFieldReader<u64>),
+    I64s(MultiValuedFastFieldReader<i64>),
+    F64s(MultiValuedFastFieldReader<f64>),
+    Dates(MultiValuedFastFieldReader<tantivy::DateTime>),
+}
+
+impl ValuesReader {
+    fn open(
+        segment: &tantivy::SegmentReader,
+        field_name: &str,
+    ) -> tantivy::Result<(Self, ValueKind)> {
+        let field = segment_field(segment, field_name)?;
+        let fast_fields = segment.fast_fields();
+
+        let field_type = segment.schema().get_field_entry(field).field_type().clone();
+
+        let cardinality = match &field_type {
+            FieldType::U64(options)
+            | FieldType::I64(options)
+            | FieldType::F64(options)
+            | FieldType::Date(options) => options.get_fastfield_cardinality(),
+            _ => {
+                return Err(tantivy::TantivyError::SchemaError(format!(
+                    "Field {:?} cannot be aggregated",
+                    field_name
+                )))
+            }
+        };
+        let multi = cardinality == Some(Cardinality::MultiValues);
+
+        Ok(match (field_type, multi) {
+            (FieldType::U64(_), false) => {
+                (ValuesReader::U64(fast_fields.u64(field)?), ValueKind::U64)
+            }
+            (FieldType::I64(_), false) => {
+                (ValuesReader::I64(fast_fields.i64(field)?), ValueKind::I64)
+            }
+            (FieldType::F64(_), false) => {
+                (ValuesReader::F64(fast_fields.f64(field)?), ValueKind::F64)
+            }
+            (FieldType::Date(_), false) => {
+                (ValuesReader::Date(fast_fields.date(field)?), ValueKind::Date)
+            }
+            (FieldType::U64(_), true) => {
+                (ValuesReader::U64s(fast_fields.u64s(field)?), ValueKind::U64)
+            }
+            (FieldType::I64(_), true) => {
+                (ValuesReader::I64s(fast_fields.i64s(field)?), ValueKind::I64)
+            }
+            (FieldType::F64(_), true) => {
+                (ValuesReader::F64s(fast_fields.f64s(field)?), ValueKind::F64)
+            }
+            _ => (ValuesReader::Dates(fast_fields.dates(field)?), ValueKind::Date),
+        })
+    }
+
+    // Replace the contents of `values` with the values of `doc`.
+    fn read(&self, doc: tantivy::DocId, values: &mut Vec<u64>) {
+        fn read_multi<V: FastValue>(
+            reader: &MultiValuedFastFieldReader<V>,
+            doc: tantivy::DocId,
+            values: &mut Vec<u64>,
+        ) {
+            let mut typed = Vec::new();
+            reader.get_vals(doc, &mut typed);
+            values.extend(typed.iter().map(FastValue::to_u64));
+        }
+
+        values.clear();
+
+        match self {
+            ValuesReader::U64(reader) => values.push(reader.get(doc)),
+            ValuesReader::I64(reader) => values.push(reader.get(doc).to_u64()),
+            ValuesReader::F64(reader) => values.push(reader.get(doc).to_u64()),
+            ValuesReader::Date(reader) => values.push(reader.get(doc).to_u64()),
+            ValuesReader::U64s(reader) => reader.get_vals(doc, values),
+            ValuesReader::I64s(reader) => read_multi(reader, doc, values),
+            ValuesReader::F64s(reader) => read_multi(reader, doc, values),
+            ValuesReader::Dates(reader) => read_multi(reader, doc, values),
+        }
+    }
+}
+
+fn segment_field(
+    segment: &tantivy::SegmentReader,
+    field_name: &str,
+) -> tantivy::Result<tantivy::schema::Field> {
+    segment.schema().get_field(field_name).ok_or_else(|| {
+        tantivy::TantivyError::SchemaError(format!("Unknown field `{}`", field_name))
+    })
+}
+
+// Used by the aggregation collectors.
+#[doc(hidden)]
+pub struct AggregationSegmentCollector {
+    source: Source,
+    buffer: Vec<u64>,
+    state: SegmentState,
+}
+
+// Where a segment collector reads values from.
+enum Source {
+    Values(ValuesReader, ValueKind),
+    Facets(tantivy::fastfield::FacetReader),
+}
+
+enum SegmentState {
+    Stats(FieldStats),
+    Histogram(f64, HashMap<i64, u64>),
+    DateHistogram(DateInterval, HashMap<i64, u64>),
+    Terms(HashMap<u64, u64>),
+}
+
+// Used by the aggregation collectors.
+#[doc(hidden)]
+pub struct AggregationSegmentFruit(SegmentFruit);
+
+enum SegmentFruit {
+    Stats(FieldStats),
+    Histogram(HashMap<i64, u64>),
+    DateHistogram(HashMap<i64, u64>),
+    Terms(HashMap<RawTermKey, u64>),
+}
+
+// Value counted by `Terms`, numeric values are kept as order-preserving `u64`s until merged.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum RawTermKey {
+    Value(ValueKind, u64),
+    Facet(String),
+}
+
+impl RawTermKey {
+    fn into_term_key(self) -> TermKey {
+        match self {
+            RawTermKey::Value(kind, value) => kind.to_term_key(value),
+            RawTermKey::Facet(facet) => TermKey::Facet(facet),
+        }
+    }
+}
+
+impl Aggregation {
+    fn open(
+        &self,
+        segment: &tantivy::SegmentReader,
+    ) -> tantivy::Result<AggregationSegmentCollector> {
+        let values = || {
+            ValuesReader::open(segment, self.field_name())
+                .map(|(reader, kind)| Source::Values(reader, kind))
+        };
+
+        let (source, state) = match self {
+            Aggregation::Stats(_) => (values()?, SegmentState::Stats(FieldStats::default())),
+            Aggregation::Histogram(histogram) => {
+                if !(histogram.interval > 0.0 && histogram.interval.is_finite()) {
+                    return Err(tantivy::TantivyError::InvalidArgument(format!(
+                        "Invalid histogram interval {}",
+                        histogram.interval
+                    )));
+                }
+                (values()?, SegmentState::Histogram(histogram.interval, HashMap::new()))
+            }
+            Aggregation::DateHistogram(date_histogram) => {
+                let source = values()?;
+                if !matches!(source, Source::Values(_, ValueKind::Date)) {
+                    return Err(tantivy::TantivyError::SchemaError(format!(
+                        "Field {:?} is not a date field",
+                        date_histogram.field_name
+                    )));
+                }
+                (source, SegmentState::DateHistogram(date_histogram.interval, HashMap::new()))
+            }
+            Aggregation::Terms(terms) => {
+                let field = segment_field(segment, &terms.field_name)?;
+                let source = match segment.schema().get_field_entry(field).field_type() {
+                    FieldType::HierarchicalFacet => Source::Facets(segment.facet_reader(field)?),
+                    _ => values()?,
+                };
+                (source, SegmentState::Terms(HashMap::new()))
+            }
+        };
+
+        Ok(AggregationSegmentCollector { source, buffer: Vec::new(), state })
+    }
+
+    fn merge(&self, segment_fruits: Vec<AggregationSegmentFruit>) -> AggregationResult {
+        fn merge_counts<K: Eq + std::hash::Hash>(
+            counts: impl Iterator<Item = HashMap<K, u64>>,
+        ) -> HashMap<K, u64> {
+            let mut out = HashMap::new();
+            for (key, count) in counts.flatten() {
+                *out.entry(key).or_insert(0) += count;
+            }
+            out
+        }
+
+        fn sorted<K: Ord>(counts: HashMap<K, u64>) -> Vec<(K, u64)> {
+            let mut out = counts.into_iter().collect::<Vec<_>>();
+            out.sort_by(|a, b| a.0.cmp(&b.0));
+            out
+        }
+
+        match self {
+            Aggregation::Stats(_) => {
+                let mut out = FieldStats::default();
+                for fruit in segment_fruits {
+                    if let SegmentFruit::Stats(stats) = fruit.0 {
+                        out.count += stats.count;
+                        out.sum += stats.sum;
+                        out.min = out.min.into_iter().chain(stats.min).reduce(f64::min);
+                        out.max = out.max.into_iter().chain(stats.max).reduce(f64::max);
+                    }
+                }
+                out.avg = Some(out.sum / out.count as f64).filter(|_| out.count > 0);
+                AggregationResult::Stats(out)
+            }
+            Aggregation::Histogram(histogram) => {
+                let counts =
+                    merge_counts(segment_fruits.into_iter().filter_map(|fruit| match fruit.0 {
+                        SegmentFruit::Histogram(counts) => Some(counts),
+                        _ => None,
+                    }));
+                let buckets = sorted(counts)
+                    .into_iter()
+                    .map(|(idx, count)| HistogramBucket {
+                        key: idx as f64 * histogram.interval,
+                        count,
+                    })
+                    .collect();
+                AggregationResult::Histogram(buckets)
+            }
+            Aggregation::DateHistogram(_) => {
+                let counts =
+                    merge_counts(segment_fruits.into_iter().filter_map(|fruit| match fruit.0 {
+                        SegmentFruit::DateHistogram(counts) => Some(counts),
+                        _ => None,
+                    }));
+                let buckets = sorted(counts)
+                    .into_iter()
+                    .map(|(key, count)| DateHistogramBucket {
+                        key,
+                        key_as_string: chrono::DateTime::from_timestamp(key, 0)
+                            .map(|date| date.to_rfc3339())
+                            .unwrap_or_default(),
+                        count,
+                    })
+                    .collect();
+                AggregationResult::DateHistogram(buckets)
+            }
+            Aggregation::Terms(terms) => {
+                let counts =
+                    merge_counts(segment_fruits.into_iter().filter_map(|fruit| match fruit.0 {
+                        SegmentFruit::Terms(counts) => Some(counts),
+                        _ => None,
+                    }));
+                let mut counts = counts.into_iter().collect::<Vec<_>>();
+                counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                let buckets = counts
+                    .into_iter()
+                    .take(terms.size)
+                    .map(|(key, count)| TermsBucket { key: key.into_term_key(), count })
+                    .collect();
+                AggregationResult::Terms(buckets)
+            }
+        }
+    }
+}
+
+impl tantivy::collector::SegmentCollector for AggregationSegmentCollector {
+    type Fruit = AggregationSegmentFruit;
+
+    fn collect(&mut self, doc: tantivy::DocId, _score: tantivy::Score) {
+        let (reader, kind) = match &self.source {
+            Source::Values(reader, kind) => (reader, *kind),
+            Source::Facets(reader) => {
+                reader.facet_ords(doc, &mut self.buffer);
+                if let SegmentState::Terms(counts) = &mut self.state {
+                    for ord in &self.buffer {
+                        *counts.entry(*ord).or_insert(0) += 1;
+                    }
+                }
+                return;
+            }
+        };
+
+        reader.read(doc, &mut self.buffer);
+
+        for value in &self.buffer {
+            match &mut self.state {
+                SegmentState::Stats(stats) => {
+                    let value = kind.to_f64(*value);
+                    stats.count += 1;
+                    stats.sum += value;
+                    stats.min = Some(stats.min.map_or(value, |min| min.min(value)));
+                    stats.max = Some(stats.max.map_or(value, |max| max.max(value)));
+                }
+                SegmentState::Histogram(interval, counts) => {
+                    let value = kind.to_f64(*value);
+                    if !value.is_nan() {
+                        *counts.entry((value / *interval).floor() as i64).or_insert(0) += 1;
+                    }
+                }
+                SegmentState::DateHistogram(interval, counts) => {
+                    let key = interval.truncate(i64::from_u64(*value));
+                    *counts.entry(key).or_insert(0) += 1;
+                }
+                SegmentState::Terms(counts) => *counts.entry(*value).or_insert(0) += 1,
+            }
+        }
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        AggregationSegmentFruit(match self.state {
+            SegmentState::Stats(stats) => SegmentFruit::Stats(stats),
+            SegmentState::Histogram(_, counts) => SegmentFruit::Histogram(counts),
+            SegmentState::DateHistogram(_, counts) => SegmentFruit::DateHistogram(counts),
+            SegmentState::Terms(counts) => {
+                let counts = match self.source {
+                    Source::Values(_, kind) => counts
+                        .into_iter()
+                        .map(|(value, count)| (RawTermKey::Value(kind, value), count))
+                        .collect(),
+                    Source::Facets(mut reader) => {
+                        let mut facet = tantivy::schema::Facet::root();
+                        counts
+                            .into_iter()
+                            .filter_map(|(ord, count)| {
+                                reader.facet_from_ord(ord, &mut facet).ok()?;
+                                Some((RawTermKey::Facet(facet.to_string()), count))
+                            })
+                            .collect()
+                    }
+                };
+                SegmentFruit::Terms(counts)
+            }
+        })
+    }
+}
+
+impl tantivy::collector::Collector for Aggregation {
+    type Fruit = AggregationResult;
+    type Child = AggregationSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: tantivy::SegmentLocalId,
+        segment: &tantivy::SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        self.open(segment)
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<AggregationSegmentFruit>,
+    ) -> tantivy::Result<Self::Fruit> {
+        Ok(self.merge(segment_fruits))
+    }
+}
+
+macro_rules! aggregation_collector {
+    ($collector:ident, $variant:ident, $fruit:ty) => {
+        impl tantivy::collector::Collector for $collector {
+            type Fruit = $fruit;
+            type Child = AggregationSegmentCollector;
+
+            fn for_segment(
+                &self,
+                _segment_local_id: tantivy::SegmentLocalId,
+                segment: &tantivy::SegmentReader,
+            ) -> tantivy::Result<Self::Child> {
+                Aggregation::from(self.clone()).open(segment)
+            }
+
+            fn requires_scoring(&self) -> bool {
+                false
+            }
+
+            fn merge_fruits(
+                &self,
+                segment_fruits: Vec<AggregationSegmentFruit>,
+            ) -> tantivy::Result<Self::Fruit> {
+                match Aggregation::from(self.clone()).merge(segment_fruits) {
+                    AggregationResult::$variant(out) => Ok(out),
+                    _ => unreachable!(),
+                }
+            }
+        }
+    };
+}
+
+aggregation_collector!(Stats, Stats, FieldStats);
+aggregation_collector!(Histogram, Histogram, Vec<HistogramBucket>);
+aggregation_collector!(DateHistogram, DateHistogram, Vec<DateHistogramBucket>);
+aggregation_collector!(Terms, Terms, Vec<TermsBucket>);
+
+// Used by the `Aggregations` collector.
+#[doc(hidden)]
+pub struct AggregationsSegmentCollector(Vec<AggregationSegmentCollector>);
+
+impl tantivy::collector::SegmentCollector for AggregationsSegmentCollector {
+    type Fruit = Vec<AggregationSegmentFruit>;
+
+    fn collect(&mut self, doc: tantivy::DocId, score: tantivy::Score) {
+        for collector in &mut self.0 {
+            collector.collect(doc, score);
+        }
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        self.0.into_iter().map(tantivy::collector::SegmentCollector::harvest).collect()
+    }
+}
+
+impl tantivy::collector::Collector for Aggregations {
+    type Fruit = BTreeMap<String, AggregationResult>;
+    type Child = AggregationsSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: tantivy::SegmentLocalId,
+        segment: &tantivy::SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        let collectors = self
+            .aggregations
+            .iter()
+            .map(|(_, aggregation)| aggregation.open(segment))
+            .collect::<tantivy::Result<Vec<_>>>()?;
+
+        Ok(AggregationsSegmentCollector(collectors))
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<Vec<AggregationSegmentFruit>>,
+    ) -> tantivy::Result<Self::Fruit> {
+        let mut by_aggregation = self.aggregations.iter().map(|_| Vec::new()).collect::<Vec<_>>();
+
+        for fruits in segment_fruits {
+            for (idx, fruit) in fruits.into_iter().enumerate() {
+                by_aggregation[idx].push(fruit);
+            }
+        }
+
+        Ok(self
+            .aggregations
+            .iter()
+            .zip(by_aggregation)
+            .map(|((name, aggregation), fruits)| (name.clone(), aggregation.merge(fruits)))
+            .collect())
+    }
+}
+
+/// Search results container, with the results of the requested aggregations by name
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AggregatedResults<T> {
+    pub results: Results<T>,
+    pub aggregations: BTreeMap<String, AggregationResult>,
+}
+
+/**
+Searcher that returns aggregations over the matching documents alongside the search results
+
+Aggregations cover every match, while hits are only loaded for the requested `Page`.
+
+## Usage:
+
+```rust
+use pallet::{err, search, DocumentLike, Store};
+
+fn dashboard<T>(store: &Store<T>, query: &str) -> err::Result<search::AggregatedResults<T>>
+where
+    T: DocumentLike + Send,
+    T::IndexFieldsType: Sync,
+{
+    let aggregated = search::Aggregated::new(query)
+        .with_aggregation("rating", search::Stats::new("rating"))
+        .with_aggregation("per_year", search::Histogram::new("year", 1.0))
+        .with_aggregation("top_categories", search::Terms::new("category", 10))
+        .with_limit(20);
+
+    store.search(aggregated)
+}
+```
+*/
+pub struct Aggregated<Q> {
+    pub(crate) query: Q,
+    pub(crate) aggregations: Aggregations,
+    pub(crate) page: Page,
+}
+
+impl<Q> Aggregated<Q> {
+    /// Create a new `Aggregated` searcher.
+    pub fn new(query: Q) -> Self {
+        Aggregated { query, aggregations: Aggregations::new(), page: Page::default() }
+    }
+
+    /// Add an aggregation, whose result is returned under `name`.
+    pub fn with_aggregation<I: Into<String>, A: Into<Aggregation>>(
+        mut self,
+        name: I,
+        aggregation: A,
+    ) -> Self {
+        self.aggregations = self.aggregations.with_aggregation(name, aggregation);
+        self
+    }
+
+    /// Set the page of results to return.
+    pub fn with_page(mut self, page: Page) -> Self {
+        self.page = page;
+        self
+    }
+
+    /// Set the number of results to skip.
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.page.offset = offset;
+        self
+    }
+
+    /// Set the maximum number of results to return.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.page.limit = limit;
+        self
+    }
+}
+
+impl<Q, T> Searcher<T> for Aggregated<Q>
+where
+    Q: AsQuery,
+    T: DocumentLike + Send,
+    T::IndexFieldsType: Sync,
+{
+    type Item = AggregatedResults<T>;
+    type Error = err::Error;
+
+    fn search(&self, store: &Store<T>) -> Result<Self::Item, Self::Error> {
+        for (_, aggregation) in &self.aggregations.aggregations {
+            store.index.field(aggregation.field_name())?;
+        }
+
+        let (count_handle, scored_ids_handle) = Page::collector(store.index.id_field);
+
+        let query = self.query.as_query(&store.index.inner, &store.index.default_search_fields)?;
+
+        let search_params = Params::default()
+            .with_query(query)
+            .with_collector((count_handle, (scored_ids_handle, self.aggregations.clone())))
+            .with_handler(|(count, (scored_ids, aggregations))| -> Result<_, err::Error> {
+                let hits = self.page.load_hits(store, scored_ids)?;
+
+                Ok(AggregatedResults { results: Results { count, hits }, aggregations })
+            });
+
+        search_params.search(store)
+    }
+}
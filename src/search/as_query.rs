@@ -20,7 +20,7 @@ pub trait AsQuery {
         &self,
         index: &tantivy::Index,
         default_search_fields: &[tantivy::schema::Field],
-    ) -> err::Result<QueryContainer>;
+    ) -> err::Result<QueryContainer<'_>>;
 }
 
 impl AsQuery for str {
@@ -28,7 +28,7 @@ impl AsQuery for str {
         &self,
         index: &tantivy::Index,
         default_search_fields: &[tantivy::schema::Field],
-    ) -> err::Result<QueryContainer> {
+    ) -> err::Result<QueryContainer<'_>> {
         let query_parser =
             tantivy::query::QueryParser::for_index(index, default_search_fields.into());
 
@@ -43,7 +43,7 @@ impl AsQuery for Box<dyn tantivy::query::Query> {
         &self,
         _index: &tantivy::Index,
         _default_search_fields: &[tantivy::schema::Field],
-    ) -> err::Result<QueryContainer> {
+    ) -> err::Result<QueryContainer<'_>> {
         Ok(QueryContainer::Ref(self))
     }
 }
@@ -53,7 +53,7 @@ impl<'a> AsQuery for QueryContainer<'a> {
         &self,
         _index: &tantivy::Index,
         _default_search_fields: &[tantivy::schema::Field],
-    ) -> err::Result<QueryContainer> {
+    ) -> err::Result<QueryContainer<'_>> {
         Ok(QueryContainer::Ref(self.as_ref()))
     }
 }
@@ -66,7 +66,7 @@ where
         &self,
         index: &tantivy::Index,
         default_search_fields: &[tantivy::schema::Field],
-    ) -> err::Result<QueryContainer> {
+    ) -> err::Result<QueryContainer<'_>> {
         AsQuery::as_query(*self, index, default_search_fields)
     }
 }
@@ -0,0 +1,133 @@
+use crate::search::{self, AsQuery, Page, Params, Results, Searcher};
+use crate::{err, DocumentLike, Store};
+
+/// Number of matching documents for a single facet
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FacetCount {
+    pub facet: String,
+    pub count: u64,
+}
+
+/// Search results container, with counts for the requested facets
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FacetedResults<T> {
+    pub results: Results<T>,
+    pub facet_counts: Vec<FacetCount>,
+}
+
+/**
+Searcher that returns facet counts alongside the search results
+
+Counts are returned for the direct children of each requested facet (or of the root facet,
+if none are requested), over every match rather than just the requested `Page`.
+
+## Usage:
+
+```rust
+use pallet::{err, search, DocumentLike, Store};
+
+fn with_categories<T>(store: &Store<T>, query: &str) -> err::Result<search::FacetedResults<T>>
+where
+    T: DocumentLike + Send,
+    T::IndexFieldsType: Sync,
+{
+    let faceted = search::Faceted::new(query, "category").with_facet("/fiction").with_limit(20);
+
+    store.search(faceted)
+}
+```
+*/
+pub struct Faceted<Q> {
+    pub(crate) query: Q,
+    pub(crate) field_name: String,
+    pub(crate) facets: Vec<String>,
+    pub(crate) page: Page,
+}
+
+impl<Q> Faceted<Q> {
+    /// Create a new `Faceted` searcher, counting facets for the given field.
+    pub fn new<I: Into<String>>(query: Q, field_name: I) -> Self {
+        Faceted { query, field_name: field_name.into(), facets: Vec::new(), page: Page::default() }
+    }
+
+    /// Add a facet whose children should be counted, e.g. `/category`.
+    ///
+    /// Facets must not be ancestors or descendants of each other.
+    pub fn with_facet<I: Into<String>>(mut self, facet: I) -> Self {
+        self.facets.push(facet.into());
+        self
+    }
+
+    /// Set the page of results to return.
+    pub fn with_page(mut self, page: Page) -> Self {
+        self.page = page;
+        self
+    }
+
+    /// Set the number of results to skip.
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.page.offset = offset;
+        self
+    }
+
+    /// Set the maximum number of results to return.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.page.limit = limit;
+        self
+    }
+}
+
+impl<Q, T> Searcher<T> for Faceted<Q>
+where
+    Q: AsQuery,
+    T: DocumentLike + Send,
+    T::IndexFieldsType: Sync,
+{
+    type Item = FacetedResults<T>;
+    type Error = err::Error;
+
+    fn search(&self, store: &Store<T>) -> Result<Self::Item, Self::Error> {
+        let facets = if self.facets.is_empty() {
+            vec![tantivy::schema::Facet::root()]
+        } else {
+            self.facets
+                .iter()
+                .map(|facet| search::Facet::from(facet.as_str()).to_tantivy_facet())
+                .collect::<Vec<_>>()
+        };
+
+        for (idx, facet) in facets.iter().enumerate() {
+            if facets[idx + 1..].iter().any(|x| x.is_prefix_of(facet) || facet.is_prefix_of(x)) {
+                return Err(err::custom(format!("Overlapping facet `{}`", facet)));
+            }
+        }
+
+        let mut facet_handle =
+            tantivy::collector::FacetCollector::for_field(store.index.field(&self.field_name)?);
+
+        for facet in &facets {
+            facet_handle.add_facet(facet.clone());
+        }
+
+        let (count_handle, scored_ids_handle) = Page::collector(store.index.id_field);
+
+        let query = self.query.as_query(&store.index.inner, &store.index.default_search_fields)?;
+
+        let search_params = Params::default()
+            .with_query(query)
+            .with_collector((count_handle, (scored_ids_handle, facet_handle)))
+            .with_handler(|(count, (scored_ids, facet_counts))| -> Result<_, err::Error> {
+                let hits = self.page.load_hits(store, scored_ids)?;
+
+                let facet_counts = facets
+                    .iter()
+                    .flat_map(|facet| facet_counts.get(facet.clone()))
+                    .map(|(facet, count)| FacetCount { facet: facet.to_string(), count })
+                    .collect();
+
+                Ok(FacetedResults { results: Results { count, hits }, facet_counts })
+            });
+
+        search_params.search(store)
+    }
+}
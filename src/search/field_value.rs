@@ -1,15 +1,18 @@
 pub trait FieldValue: Clone {
     type FieldOptionsType;
+    /// The single value type, for `search::TypedField` query helpers.
+    type Item;
     fn default_field_options() -> Self::FieldOptionsType;
     fn field_entry<I: Into<String>, T: Into<Self::FieldOptionsType>>(
         name: I,
         field_options: Option<T>,
     ) -> tantivy::schema::FieldEntry;
-    fn into_value(self) -> Option<tantivy::schema::Value>;
+    fn into_values(self) -> Vec<tantivy::schema::Value>;
 }
 
 impl FieldValue for String {
     type FieldOptionsType = tantivy::schema::TextOptions;
+    type Item = Self;
 
     fn default_field_options() -> Self::FieldOptionsType {
         tantivy::schema::TEXT
@@ -25,13 +28,14 @@ impl FieldValue for String {
         )
     }
 
-    fn into_value(self) -> Option<tantivy::schema::Value> {
-        Some(self.into())
+    fn into_values(self) -> Vec<tantivy::schema::Value> {
+        vec![self.into()]
     }
 }
 
 impl FieldValue for u64 {
     type FieldOptionsType = tantivy::schema::IntOptions;
+    type Item = Self;
 
     fn default_field_options() -> Self::FieldOptionsType {
         tantivy::schema::INDEXED.into()
@@ -47,13 +51,14 @@ impl FieldValue for u64 {
         )
     }
 
-    fn into_value(self) -> Option<tantivy::schema::Value> {
-        Some(self.into())
+    fn into_values(self) -> Vec<tantivy::schema::Value> {
+        vec![self.into()]
     }
 }
 
 impl FieldValue for i64 {
     type FieldOptionsType = tantivy::schema::IntOptions;
+    type Item = Self;
 
     fn default_field_options() -> Self::FieldOptionsType {
         tantivy::schema::INDEXED.into()
@@ -69,13 +74,14 @@ impl FieldValue for i64 {
         )
     }
 
-    fn into_value(self) -> Option<tantivy::schema::Value> {
-        Some(self.into())
+    fn into_values(self) -> Vec<tantivy::schema::Value> {
+        vec![self.into()]
     }
 }
 
 impl FieldValue for f64 {
     type FieldOptionsType = tantivy::schema::IntOptions;
+    type Item = Self;
 
     fn default_field_options() -> Self::FieldOptionsType {
         tantivy::schema::INDEXED.into()
@@ -91,13 +97,14 @@ impl FieldValue for f64 {
         )
     }
 
-    fn into_value(self) -> Option<tantivy::schema::Value> {
-        Some(self.into())
+    fn into_values(self) -> Vec<tantivy::schema::Value> {
+        vec![self.into()]
     }
 }
 
 impl FieldValue for tantivy::DateTime {
     type FieldOptionsType = tantivy::schema::IntOptions;
+    type Item = Self;
 
     fn default_field_options() -> Self::FieldOptionsType {
         tantivy::schema::INDEXED.into()
@@ -113,13 +120,14 @@ impl FieldValue for tantivy::DateTime {
         )
     }
 
-    fn into_value(self) -> Option<tantivy::schema::Value> {
-        Some(self.into())
+    fn into_values(self) -> Vec<tantivy::schema::Value> {
+        vec![self.into()]
     }
 }
 
 impl<F: FieldValue> FieldValue for Option<F> {
     type FieldOptionsType = F::FieldOptionsType;
+    type Item = F::Item;
     fn default_field_options() -> Self::FieldOptionsType {
         F::default_field_options()
     }
@@ -130,7 +138,135 @@ impl<F: FieldValue> FieldValue for Option<F> {
         F::field_entry(name, field_options)
     }
 
-    fn into_value(self) -> Option<tantivy::schema::Value> {
-        self.and_then(FieldValue::into_value)
+    fn into_values(self) -> Vec<tantivy::schema::Value> {
+        self.map(FieldValue::into_values).unwrap_or_default()
+    }
+}
+
+impl<F: FieldValue> FieldValue for Vec<F> {
+    type FieldOptionsType = F::FieldOptionsType;
+    type Item = F::Item;
+    fn default_field_options() -> Self::FieldOptionsType {
+        F::default_field_options()
+    }
+    fn field_entry<I: Into<String>, T: Into<Self::FieldOptionsType>>(
+        name: I,
+        field_options: Option<T>,
+    ) -> tantivy::schema::FieldEntry {
+        multi_valued(F::field_entry(name, field_options))
+    }
+
+    fn into_values(self) -> Vec<tantivy::schema::Value> {
+        self.into_iter().flat_map(FieldValue::into_values).collect()
+    }
+}
+
+impl<F: FieldValue + Eq + std::hash::Hash, S: std::hash::BuildHasher + Clone> FieldValue
+    for std::collections::HashSet<F, S>
+{
+    type FieldOptionsType = F::FieldOptionsType;
+    type Item = F::Item;
+    fn default_field_options() -> Self::FieldOptionsType {
+        F::default_field_options()
+    }
+    fn field_entry<I: Into<String>, T: Into<Self::FieldOptionsType>>(
+        name: I,
+        field_options: Option<T>,
+    ) -> tantivy::schema::FieldEntry {
+        multi_valued(F::field_entry(name, field_options))
+    }
+
+    fn into_values(self) -> Vec<tantivy::schema::Value> {
+        self.into_iter().flat_map(FieldValue::into_values).collect()
+    }
+}
+
+impl<F: FieldValue + Ord> FieldValue for std::collections::BTreeSet<F> {
+    type FieldOptionsType = F::FieldOptionsType;
+    type Item = F::Item;
+    fn default_field_options() -> Self::FieldOptionsType {
+        F::default_field_options()
+    }
+    fn field_entry<I: Into<String>, T: Into<Self::FieldOptionsType>>(
+        name: I,
+        field_options: Option<T>,
+    ) -> tantivy::schema::FieldEntry {
+        multi_valued(F::field_entry(name, field_options))
+    }
+
+    fn into_values(self) -> Vec<tantivy::schema::Value> {
+        self.into_iter().flat_map(FieldValue::into_values).collect()
+    }
+}
+
+// Fast fields of collections hold several values per document, so must be multi-valued.
+fn multi_valued(entry: tantivy::schema::FieldEntry) -> tantivy::schema::FieldEntry {
+    use tantivy::schema::{Cardinality, FieldEntry, FieldType, IntOptions};
+
+    let options = |options: &IntOptions| match options.get_fastfield_cardinality() {
+        Some(_) => options.clone().set_fast(Cardinality::MultiValues),
+        None => options.clone(),
+    };
+
+    let name = entry.name().to_string();
+
+    match entry.field_type() {
+        FieldType::U64(x) => FieldEntry::new_u64(name, options(x)),
+        FieldType::I64(x) => FieldEntry::new_i64(name, options(x)),
+        FieldType::F64(x) => FieldEntry::new_f64(name, options(x)),
+        FieldType::Date(x) => FieldEntry::new_date(name, options(x)),
+        _ => entry,
+    }
+}
+
+/// A hierarchical facet value, e.g. `/category/sub`
+///
+/// Paths without a leading `/` are treated as relative to the root, and empty paths are not indexed.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+pub struct Facet(pub String);
+
+impl From<String> for Facet {
+    fn from(t: String) -> Self {
+        Facet(t)
+    }
+}
+
+impl From<&str> for Facet {
+    fn from(t: &str) -> Self {
+        Facet(t.into())
+    }
+}
+
+impl Facet {
+    pub(crate) fn to_tantivy_facet(&self) -> tantivy::schema::Facet {
+        match self.0.as_str() {
+            "" => tantivy::schema::Facet::root(),
+            path if path.starts_with('/') => tantivy::schema::Facet::from_text(path),
+            path => tantivy::schema::Facet::from_text(&format!("/{}", path)),
+        }
+    }
+}
+
+impl FieldValue for Facet {
+    type FieldOptionsType = ();
+    type Item = Self;
+
+    fn default_field_options() -> Self::FieldOptionsType {}
+
+    fn field_entry<I: Into<String>, T: Into<Self::FieldOptionsType>>(
+        name: I,
+        _field_options: Option<T>,
+    ) -> tantivy::schema::FieldEntry {
+        tantivy::schema::FieldEntry::new_facet(name.into())
+    }
+
+    fn into_values(self) -> Vec<tantivy::schema::Value> {
+        if self.0.is_empty() {
+            Vec::new()
+        } else {
+            vec![self.to_tantivy_facet().into()]
+        }
     }
 }
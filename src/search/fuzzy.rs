@@ -0,0 +1,186 @@
+use crate::err;
+use crate::search::as_query::QueryContainer;
+use crate::search::query::{term_query, text_terms};
+use crate::search::AsQuery;
+use tantivy::query::{BooleanQuery, EmptyQuery, FuzzyTermQuery, Occur};
+use tantivy::schema::{Field, FieldType};
+
+/**
+Typo-tolerant query over text fields
+
+Each term of the text matches indexed terms within an edit distance that depends on its length,
+by default 1 edit for terms of at least 5 characters and 2 edits for terms of at least 9
+characters (a transposition counts as 2 edits). Terms are searched in the default search fields
+(or those given with `with_field`), exact matches score higher than fuzzy ones, and by default
+documents matching any term are returned, as with query strings.
+
+Implements `AsQuery`, so can be used with `Store::search` directly, or with other searchers
+such as `Paged`.
+
+## Usage:
+
+```rust
+use pallet::{err, search, DocumentLike, Store};
+
+fn search_as_typed<T>(store: &Store<T>, text: &str) -> err::Result<search::Results<T>>
+where
+    T: DocumentLike + Send,
+    T::IndexFieldsType: Sync,
+{
+    let fuzzy = search::Fuzzy::new(text).with_prefix(true).with_conjunction(true);
+
+    store.search(search::Paged::new(fuzzy))
+}
+```
+*/
+#[derive(Debug, Clone)]
+pub struct Fuzzy {
+    pub(crate) text: String,
+    pub(crate) distances: Vec<(usize, u8)>,
+    pub(crate) prefix: bool,
+    pub(crate) conjunction: bool,
+    pub(crate) field_names: Vec<String>,
+}
+
+impl Fuzzy {
+    /// Create a new `Fuzzy` query for `text`.
+    pub fn new<I: Into<String>>(text: I) -> Self {
+        Fuzzy {
+            text: text.into(),
+            distances: vec![(5, 1), (9, 2)],
+            prefix: false,
+            conjunction: false,
+            field_names: Vec::new(),
+        }
+    }
+
+    /// Allow `distance` edits (at most 2) for terms of at least `min_len` characters, up to the
+    /// next longer `min_len`.
+    ///
+    /// For example, `with_distance(9, 1)` allows at most 1 edit for any term.
+    pub fn with_distance(mut self, min_len: usize, distance: u8) -> Self {
+        self.distances.retain(|(len, _)| *len != min_len);
+        self.distances.push((min_len, distance));
+        self
+    }
+
+    /// Match the last term of the text as a prefix, for search-as-you-type.
+    pub fn with_prefix(mut self, prefix: bool) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Require documents to match every term of the text, instead of any term.
+    pub fn with_conjunction(mut self, conjunction: bool) -> Self {
+        self.conjunction = conjunction;
+        self
+    }
+
+    /// Search this field instead of the default search fields, may be called more than once.
+    pub fn with_field<I: Into<String>>(mut self, field_name: I) -> Self {
+        self.field_names.push(field_name.into());
+        self
+    }
+
+    // Allowed edits for a term of `len` characters.
+    fn distance(&self, len: usize) -> u8 {
+        self.distances
+            .iter()
+            .filter(|(min_len, _)| len >= *min_len)
+            .max_by_key(|(min_len, _)| *min_len)
+            .map(|(_, distance)| *distance)
+            .unwrap_or(0)
+    }
+
+    // Query for a single term, matching it exactly or within the allowed edits.
+    fn term_query(
+        &self,
+        term: tantivy::Term,
+        field_type: &FieldType,
+        is_last: bool,
+    ) -> Box<dyn tantivy::query::Query> {
+        let distance = self.distance(term.text().chars().count());
+        let prefix = self.prefix && is_last;
+
+        if distance == 0 && !prefix {
+            return term_query(term, field_type);
+        }
+
+        let fuzzy = if prefix {
+            FuzzyTermQuery::new_prefix(term.clone(), distance, false)
+        } else {
+            FuzzyTermQuery::new(term.clone(), distance, false)
+        };
+
+        Box::new(BooleanQuery::new(vec![
+            (Occur::Should, term_query(term, field_type)),
+            (Occur::Should, Box::new(fuzzy)),
+        ]))
+    }
+}
+
+impl AsQuery for Fuzzy {
+    fn as_query(
+        &self,
+        index: &tantivy::Index,
+        default_search_fields: &[Field],
+    ) -> err::Result<QueryContainer<'_>> {
+        if let Some((_, distance)) = self.distances.iter().find(|(_, distance)| *distance > 2) {
+            return Err(err::custom(format!("Invalid edit distance `{}`, at most 2", distance)));
+        }
+
+        let schema = index.schema();
+
+        let fields = if self.field_names.is_empty() {
+            default_search_fields.to_vec()
+        } else {
+            self.field_names
+                .iter()
+                .map(|field_name| {
+                    schema
+                        .get_field(field_name)
+                        .ok_or_else(|| err::custom(format!("Unknown field `{}`", field_name)))
+                })
+                .collect::<err::Result<Vec<_>>>()?
+        };
+
+        // Queries for the term at each position, across all fields.
+        let mut positions: Vec<Vec<(Occur, Box<dyn tantivy::query::Query>)>> = Vec::new();
+
+        for field in fields {
+            let field_type = schema.get_field_entry(field).field_type();
+
+            if !matches!(field_type, FieldType::Str(_)) {
+                continue;
+            }
+
+            let terms = text_terms(index, field, &self.text)?;
+            let last = terms.len().saturating_sub(1);
+
+            for (idx, term) in terms.into_iter().enumerate() {
+                if positions.len() <= idx {
+                    positions.push(Vec::new());
+                }
+                positions[idx]
+                    .push((Occur::Should, self.term_query(term, field_type, idx == last)));
+            }
+        }
+
+        if positions.is_empty() {
+            return Ok(QueryContainer::Boxed(Box::new(EmptyQuery)));
+        }
+
+        let occur = if self.conjunction { Occur::Must } else { Occur::Should };
+
+        let query = BooleanQuery::new(
+            positions
+                .into_iter()
+                .map(|queries| -> (Occur, Box<dyn tantivy::query::Query>) {
+                    (occur, Box::new(BooleanQuery::new(queries)))
+                })
+                .collect(),
+        );
+
+        Ok(QueryContainer::Boxed(Box::new(query)))
+    }
+}
@@ -0,0 +1,199 @@
+use crate::search::{AsQuery, Hit, Page, Params, Searcher};
+use crate::{err, Document, DocumentLike, Store};
+use std::collections::BTreeMap;
+
+/// Highlighted fragment of a field's text
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Snippet {
+    /// The selected fragment of text.
+    pub fragment: String,
+    /// Byte ranges within `fragment` that matched the query.
+    pub highlighted: Vec<(usize, usize)>,
+    /// The fragment as HTML, with matches wrapped in `<b>` tags.
+    pub html: String,
+}
+
+/// `Document` wrapper that includes the search query score and snippets keyed by field name
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct HighlightedHit<T> {
+    pub score: f32,
+    pub doc: Document<T>,
+    pub snippets: BTreeMap<String, Snippet>,
+}
+
+/// Search results container for `Highlighted`, contains the `count` of returned results
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct HighlightedResults<T> {
+    pub count: usize,
+    pub hits: Vec<HighlightedHit<T>>,
+}
+
+/**
+Searcher that attaches highlighted snippets to each hit
+
+Snippets are generated with `tantivy::SnippetGenerator`, using the text produced by
+`DocumentLike::as_index_document` for the `Document` loaded from the tree, so fields do not need
+to be stored in the index. Fields without any matches are omitted from `snippets`. By default,
+snippets are generated for the default search fields, and only for hits within the requested
+`Page`.
+
+## Usage:
+
+```rust
+use pallet::{err, search, DocumentLike, Store};
+
+fn highlight<T>(store: &Store<T>, query: &str) -> err::Result<search::HighlightedResults<T>>
+where
+    T: DocumentLike + Send,
+    T::IndexFieldsType: Sync,
+{
+    let highlighted =
+        search::Highlighted::new(query).with_field("title").with_max_num_chars(100).with_limit(20);
+
+    store.search(highlighted)
+}
+```
+*/
+pub struct Highlighted<Q> {
+    pub(crate) query: Q,
+    pub(crate) field_names: Vec<String>,
+    pub(crate) max_num_chars: Option<usize>,
+    pub(crate) page: Page,
+}
+
+impl<Q> Highlighted<Q> {
+    /// Create a new `Highlighted` searcher.
+    pub fn new(query: Q) -> Self {
+        Highlighted { query, field_names: Vec::new(), max_num_chars: None, page: Page::default() }
+    }
+
+    /// Add a text field to generate snippets for.
+    pub fn with_field<I: Into<String>>(mut self, field_name: I) -> Self {
+        self.field_names.push(field_name.into());
+        self
+    }
+
+    /// Set the maximum length of each snippet's fragment.
+    ///
+    /// By default will use `tantivy`'s default of 150.
+    pub fn with_max_num_chars(mut self, max_num_chars: usize) -> Self {
+        self.max_num_chars = Some(max_num_chars);
+        self
+    }
+
+    /// Set the page of results to return.
+    pub fn with_page(mut self, page: Page) -> Self {
+        self.page = page;
+        self
+    }
+
+    /// Set the number of results to skip.
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.page.offset = offset;
+        self
+    }
+
+    /// Set the maximum number of results to return.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.page.limit = limit;
+        self
+    }
+}
+
+impl<Q, T> Searcher<T> for Highlighted<Q>
+where
+    Q: AsQuery,
+    T: DocumentLike + Send,
+    T::IndexFieldsType: Sync,
+{
+    type Item = HighlightedResults<T>;
+    type Error = err::Error;
+
+    fn search(&self, store: &Store<T>) -> Result<Self::Item, Self::Error> {
+        let schema = store.index.inner.schema();
+
+        let fields = if self.field_names.is_empty() {
+            store
+                .index
+                .default_search_fields
+                .iter()
+                .filter(|field| {
+                    matches!(
+                        schema.get_field_entry(**field).field_type(),
+                        tantivy::schema::FieldType::Str(_)
+                    )
+                })
+                .copied()
+                .collect::<Vec<_>>()
+        } else {
+            self.field_names
+                .iter()
+                .map(|field_name| store.index.field(field_name))
+                .collect::<err::Result<Vec<_>>>()?
+        };
+
+        let query = self.query.as_query(&store.index.inner, &store.index.default_search_fields)?;
+
+        // Released before the search runs, as the reader only holds a limited pool of searchers.
+        let searcher = store.index.reader.searcher();
+
+        let snippet_generators = fields
+            .iter()
+            .map(|field| {
+                let mut snippet_generator =
+                    tantivy::SnippetGenerator::create(&searcher, query.as_ref(), *field)?;
+                if let Some(max_num_chars) = self.max_num_chars {
+                    snippet_generator.set_max_num_chars(max_num_chars);
+                }
+                Ok((schema.get_field_name(*field).to_string(), snippet_generator))
+            })
+            .collect::<err::Result<Vec<_>>>()?;
+
+        drop(searcher);
+
+        let (count_handle, scored_ids_handle) = Page::collector(store.index.id_field);
+
+        let search_params = Params::default()
+            .with_query(&query)
+            .with_collector((count_handle, scored_ids_handle))
+            .with_handler(|(count, scored_ids)| -> Result<_, err::Error> {
+                let hits = self
+                    .page
+                    .load_hits(store, scored_ids)?
+                    .into_iter()
+                    .map(|Hit { score, doc }| {
+                        let search_doc = doc.as_index_document(&store.index.fields)?;
+
+                        let snippets = snippet_generators
+                            .iter()
+                            .map(|(field_name, snippet_generator)| {
+                                (
+                                    field_name.clone(),
+                                    snippet_generator.snippet_from_doc(&search_doc),
+                                )
+                            })
+                            .filter(|(_, snippet)| !snippet.highlighted().is_empty())
+                            .map(|(field_name, snippet)| {
+                                let snippet = Snippet {
+                                    fragment: snippet.fragments().to_string(),
+                                    highlighted: snippet
+                                        .highlighted()
+                                        .iter()
+                                        .map(|section| section.bounds())
+                                        .collect(),
+                                    html: snippet.to_html(),
+                                };
+                                (field_name, snippet)
+                            })
+                            .collect();
+
+                        Ok(HighlightedHit { score, doc, snippets })
+                    })
+                    .collect::<err::Result<Vec<_>>>()?;
+
+                Ok(HighlightedResults { count, hits })
+            });
+
+        search_params.search(store)
+    }
+}
@@ -0,0 +1,119 @@
+use crate::search::{self, AsQuery, Hit, Params, Results, ScoredId, ScoredIds, Searcher};
+use crate::{err, DocumentLike, Store};
+
+/**
+Offset and limit of the results to load, shared by every searcher that supports pagination
+
+Defaults to the first 10 results. Counts, facet counts and aggregations always cover every
+match, only the loaded `Document`s are limited.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        Page { offset: 0, limit: 10 }
+    }
+}
+
+impl Page {
+    /// Create a new `Page`, skipping `offset` results and returning at most `limit`.
+    pub fn new(offset: usize, limit: usize) -> Self {
+        Page { offset, limit }
+    }
+
+    /// Collectors for the total number of matches and their ordered `id`s.
+    pub(crate) fn collector(
+        id_field: tantivy::schema::Field,
+    ) -> (tantivy::collector::Count, ScoredIds) {
+        (tantivy::collector::Count, ScoredIds { size_hint: None, id_field })
+    }
+
+    /// Load the `Document`s within this page of the ordered `id`s.
+    pub(crate) fn load_hits<T, I>(&self, store: &Store<T>, ids: I) -> err::Result<Vec<Hit<T>>>
+    where
+        T: DocumentLike + Send,
+        T::IndexFieldsType: Sync,
+        I: IntoIterator<Item = ScoredId>,
+    {
+        search::load_hits(store, ids.into_iter().skip(self.offset).take(self.limit).collect())
+    }
+}
+
+/**
+Searcher that returns a single page of results
+
+Matching `id`s are collected and ordered as with `ScoredIds`, but only the `Document`s
+within the requested `Page` are loaded from the datastore. `Results::count` is the total
+number of matches.
+
+## Usage:
+
+```rust
+use pallet::{err, search, DocumentLike, Store};
+
+fn second_page<T>(store: &Store<T>, query: &str) -> err::Result<search::Results<T>>
+where
+    T: DocumentLike + Send,
+    T::IndexFieldsType: Sync,
+{
+    store.search(search::Paged::new(query).with_offset(20).with_limit(20))
+}
+```
+*/
+pub struct Paged<Q> {
+    pub(crate) query: Q,
+    pub(crate) page: Page,
+}
+
+impl<Q> Paged<Q> {
+    /// Create a new `Paged` searcher, defaults to the first 10 results.
+    pub fn new(query: Q) -> Self {
+        Paged { query, page: Page::default() }
+    }
+
+    /// Set the page of results to return.
+    pub fn with_page(mut self, page: Page) -> Self {
+        self.page = page;
+        self
+    }
+
+    /// Set the number of results to skip.
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.page.offset = offset;
+        self
+    }
+
+    /// Set the maximum number of results to return.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.page.limit = limit;
+        self
+    }
+}
+
+impl<Q, T> Searcher<T> for Paged<Q>
+where
+    Q: AsQuery,
+    T: DocumentLike + Send,
+    T::IndexFieldsType: Sync,
+{
+    type Item = Results<T>;
+    type Error = err::Error;
+
+    fn search(&self, store: &Store<T>) -> Result<Self::Item, Self::Error> {
+        let query = self.query.as_query(&store.index.inner, &store.index.default_search_fields)?;
+
+        let search_params = Params::default()
+            .with_query(query)
+            .with_collector(Page::collector(store.index.id_field))
+            .with_handler(|(count, scored_ids)| -> Result<_, err::Error> {
+                let hits = self.page.load_hits(store, scored_ids)?;
+                Ok(Results { count, hits })
+            });
+
+        search_params.search(store)
+    }
+}
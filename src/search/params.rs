@@ -60,6 +60,7 @@ impl<Q, C, H> Params<Q, C, H> {
 }
 
 impl<Q> Params<Q, (), ()> {
+    #[allow(clippy::type_complexity)]
     pub fn with_handler<
         O,
         C: tantivy::collector::Collector,
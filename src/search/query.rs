@@ -0,0 +1,362 @@
+use crate::err;
+use crate::search::{as_query::QueryContainer, AsQuery, Facet};
+use std::ops::{Bound, RangeBounds};
+use tantivy::query::{
+    AllQuery, BooleanQuery, BoostQuery, EmptyQuery, FuzzyTermQuery, Occur, PhraseQuery, RangeQuery,
+    TermQuery,
+};
+use tantivy::schema::{Field, FieldType, IndexRecordOption, Type};
+use tantivy::Term;
+
+/// Values used in a `Query`, converted to the type of the field they are matched against
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryValue {
+    Str(String),
+    U64(u64),
+    I64(i64),
+    F64(f64),
+    Date(tantivy::DateTime),
+    Facet(Facet),
+}
+
+macro_rules! impl_from_int {
+    ($($ty:ty => $variant:ident),*) => {
+        $(
+            impl From<$ty> for QueryValue {
+                fn from(t: $ty) -> Self {
+                    QueryValue::$variant(t.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_int!(
+    u8 => U64, u16 => U64, u32 => U64, u64 => U64,
+    i8 => I64, i16 => I64, i32 => I64, i64 => I64,
+    f32 => F64, f64 => F64
+);
+
+impl From<String> for QueryValue {
+    fn from(t: String) -> Self {
+        QueryValue::Str(t)
+    }
+}
+
+impl From<&str> for QueryValue {
+    fn from(t: &str) -> Self {
+        QueryValue::Str(t.into())
+    }
+}
+
+impl From<tantivy::DateTime> for QueryValue {
+    fn from(t: tantivy::DateTime) -> Self {
+        QueryValue::Date(t)
+    }
+}
+
+impl From<Facet> for QueryValue {
+    fn from(t: Facet) -> Self {
+        QueryValue::Facet(t)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    All,
+    Term(String, QueryValue),
+    Phrase(String, String),
+    Range(String, Bound<QueryValue>, Bound<QueryValue>),
+    Fuzzy(String, String, u8),
+    Prefix(String, String),
+    Bool(Vec<(Occur, Query)>),
+    Boost(Box<Query>, f32),
+}
+
+/**
+Typed query builder, as an alternative to `tantivy` query strings
+
+Fields are referenced by name, and values are converted to the field's type when the query is
+run. Values for text fields are split into terms using the field's tokenizer.
+
+## Usage:
+
+```rust
+use pallet::{err, search, DocumentLike, Store};
+
+fn search<T>(store: &Store<T>) -> err::Result<search::Results<T>>
+where
+    T: DocumentLike + Send,
+    T::IndexFieldsType: Sync,
+{
+    let query = search::Query::boolean()
+        .must(search::Query::phrase("title", "old man"))
+        .should(search::Query::fuzzy("description", "fish", 1).boost(2.0))
+        .must_not(search::Query::range("rating", ..8));
+
+    store.search(&query)
+}
+```
+*/
+#[derive(Debug, Clone)]
+pub struct Query(Node);
+
+impl Query {
+    /// Match all documents.
+    pub fn all() -> Self {
+        Query(Node::All)
+    }
+
+    /// Match documents where the field contains `value`.
+    ///
+    /// If a text value contains several terms, all of them must be present, in any order.
+    pub fn term<F: Into<String>, V: Into<QueryValue>>(field_name: F, value: V) -> Self {
+        Query(Node::Term(field_name.into(), value.into()))
+    }
+
+    /// Match documents where the text field contains the terms of `text` in order.
+    ///
+    /// The field must be indexed with positions.
+    pub fn phrase<F: Into<String>, I: Into<String>>(field_name: F, text: I) -> Self {
+        Query(Node::Phrase(field_name.into(), text.into()))
+    }
+
+    /// Match documents where the field has a value in `range`.
+    ///
+    /// Text values are compared as-is, without tokenizing.
+    pub fn range<F, V, R>(field_name: F, range: R) -> Self
+    where
+        F: Into<String>,
+        V: Into<QueryValue> + Clone,
+        R: RangeBounds<V>,
+    {
+        let to_bound = |bound: Bound<&V>| match bound {
+            Bound::Included(v) => Bound::Included(v.clone().into()),
+            Bound::Excluded(v) => Bound::Excluded(v.clone().into()),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        Query(Node::Range(
+            field_name.into(),
+            to_bound(range.start_bound()),
+            to_bound(range.end_bound()),
+        ))
+    }
+
+    /// Match documents where the text field contains terms within `distance` edits (at most 2)
+    /// of each term in `text`, a transposition counts as 2 edits.
+    pub fn fuzzy<F: Into<String>, I: Into<String>>(field_name: F, text: I, distance: u8) -> Self {
+        Query(Node::Fuzzy(field_name.into(), text.into(), distance))
+    }
+
+    /// Match documents where the text field contains a term starting with the last term of
+    /// `text`, and any preceding terms.
+    pub fn prefix<F: Into<String>, I: Into<String>>(field_name: F, text: I) -> Self {
+        Query(Node::Prefix(field_name.into(), text.into()))
+    }
+
+    /// Create an empty boolean query, to add clauses to with `must`, `should` and `must_not`.
+    ///
+    /// An empty boolean query matches no documents.
+    pub fn boolean() -> Self {
+        Query(Node::Bool(Vec::new()))
+    }
+
+    /// Require documents to match `query`.
+    ///
+    /// Adds a clause if this is a boolean query, otherwise creates a boolean query requiring
+    /// both this query and `query`.
+    pub fn must(self, query: Query) -> Self {
+        self.with_clause(Occur::Must, query)
+    }
+
+    /// Score documents matching `query` higher, see `must`.
+    ///
+    /// A boolean query with only `should` clauses matches documents matching any of them.
+    pub fn should(self, query: Query) -> Self {
+        self.with_clause(Occur::Should, query)
+    }
+
+    /// Exclude documents matching `query`, see `must`.
+    pub fn must_not(self, query: Query) -> Self {
+        self.with_clause(Occur::MustNot, query)
+    }
+
+    /// Multiply the scores of matching documents by `boost`.
+    pub fn boost(self, boost: f32) -> Self {
+        Query(Node::Boost(Box::new(self), boost))
+    }
+
+    fn with_clause(self, occur: Occur, query: Query) -> Self {
+        match self.0 {
+            Node::Bool(mut clauses) => {
+                clauses.push((occur, query));
+                Query(Node::Bool(clauses))
+            }
+            node => Query(Node::Bool(vec![(Occur::Must, Query(node)), (occur, query)])),
+        }
+    }
+
+    /// Build the `tantivy` query for an index.
+    pub fn to_tantivy_query(
+        &self,
+        index: &tantivy::Index,
+    ) -> err::Result<Box<dyn tantivy::query::Query>> {
+        let schema = index.schema();
+
+        let field = |field_name: &str| -> err::Result<(Field, FieldType)> {
+            let field = schema
+                .get_field(field_name)
+                .ok_or_else(|| err::custom(format!("Unknown field `{}`", field_name)))?;
+            Ok((field, schema.get_field_entry(field).field_type().clone()))
+        };
+
+        let query: Box<dyn tantivy::query::Query> = match &self.0 {
+            Node::All => Box::new(AllQuery),
+            Node::Term(field_name, value) => {
+                let (field, field_type) = field(field_name)?;
+                match (&field_type, value) {
+                    (FieldType::Str(_), QueryValue::Str(text)) => {
+                        let terms = text_terms(index, field, text)?;
+                        all_of(terms.into_iter().map(|term| term_query(term, &field_type)))
+                    }
+                    _ => term_query(to_term(field, &field_type, value)?, &field_type),
+                }
+            }
+            Node::Phrase(field_name, text) => {
+                let (field, field_type) = field(field_name)?;
+                let mut terms = text_terms(index, field, text)?;
+                match terms.len() {
+                    0 => Box::new(EmptyQuery),
+                    1 => term_query(terms.remove(0), &field_type),
+                    _ => Box::new(PhraseQuery::new(terms)),
+                }
+            }
+            Node::Range(field_name, lower, upper) => {
+                let (field, field_type) = field(field_name)?;
+                let to_bound = |bound: &Bound<QueryValue>| -> err::Result<_> {
+                    Ok(match bound {
+                        Bound::Included(v) => Bound::Included(to_term(field, &field_type, v)?),
+                        Bound::Excluded(v) => Bound::Excluded(to_term(field, &field_type, v)?),
+                        Bound::Unbounded => Bound::Unbounded,
+                    })
+                };
+                Box::new(RangeQuery::new_term_bounds(
+                    field,
+                    field_type.value_type(),
+                    &to_bound(lower)?,
+                    &to_bound(upper)?,
+                ))
+            }
+            Node::Fuzzy(field_name, text, distance) => {
+                let (field, _) = field(field_name)?;
+                let terms = text_terms(index, field, text)?;
+                all_of(terms.into_iter().map(|term| -> Box<dyn tantivy::query::Query> {
+                    Box::new(FuzzyTermQuery::new(term, *distance, false))
+                }))
+            }
+            Node::Prefix(field_name, text) => {
+                let (field, field_type) = field(field_name)?;
+                let mut terms = text_terms(index, field, text)?;
+                let last = terms.pop();
+                all_of(terms.into_iter().map(|term| term_query(term, &field_type)).chain(last.map(
+                    |term| -> Box<dyn tantivy::query::Query> {
+                        Box::new(FuzzyTermQuery::new_prefix(term, 0, false))
+                    },
+                )))
+            }
+            Node::Bool(clauses) => Box::new(BooleanQuery::new(
+                clauses
+                    .iter()
+                    .map(|(occur, query)| Ok((*occur, query.to_tantivy_query(index)?)))
+                    .collect::<err::Result<Vec<_>>>()?,
+            )),
+            Node::Boost(query, boost) => {
+                Box::new(BoostQuery::new(query.to_tantivy_query(index)?, *boost))
+            }
+        };
+
+        Ok(query)
+    }
+}
+
+impl AsQuery for Query {
+    fn as_query(
+        &self,
+        index: &tantivy::Index,
+        _default_search_fields: &[tantivy::schema::Field],
+    ) -> err::Result<QueryContainer<'_>> {
+        Ok(QueryContainer::Boxed(self.to_tantivy_query(index)?))
+    }
+}
+
+// Split text into terms with the field's tokenizer.
+pub(crate) fn text_terms(
+    index: &tantivy::Index,
+    field: Field,
+    text: &str,
+) -> err::Result<Vec<Term>> {
+    let mut terms = Vec::new();
+    index.tokenizer_for_field(field)?.token_stream(text).process(&mut |token| {
+        terms.push(Term::from_field_text(field, &token.text));
+    });
+    Ok(terms)
+}
+
+pub(crate) fn term_query(term: Term, field_type: &FieldType) -> Box<dyn tantivy::query::Query> {
+    let record_option = field_type.get_index_record_option().unwrap_or(IndexRecordOption::Basic);
+    Box::new(TermQuery::new(term, record_option))
+}
+
+// Require all queries to match, a single query is returned as-is and no queries match nothing.
+fn all_of<I>(queries: I) -> Box<dyn tantivy::query::Query>
+where
+    I: IntoIterator<Item = Box<dyn tantivy::query::Query>>,
+{
+    let mut queries = queries.into_iter().collect::<Vec<_>>();
+    match queries.len() {
+        0 => Box::new(EmptyQuery),
+        1 => queries.remove(0),
+        _ => Box::new(BooleanQuery::intersection(queries)),
+    }
+}
+
+// Convert a value to a term for the field, without tokenizing.
+fn to_term(field: Field, field_type: &FieldType, value: &QueryValue) -> err::Result<Term> {
+    let mismatch = || {
+        err::custom(format!(
+            "Value `{:?}` does not match field type `{:?}`",
+            value,
+            field_type.value_type()
+        ))
+    };
+
+    let term = match (field_type.value_type(), value) {
+        (Type::Str, QueryValue::Str(text)) => Term::from_field_text(field, text),
+        (Type::Str, QueryValue::U64(v)) => Term::from_field_text(field, &v.to_string()),
+        (Type::Str, QueryValue::I64(v)) => Term::from_field_text(field, &v.to_string()),
+        (Type::U64, QueryValue::U64(v)) => Term::from_field_u64(field, *v),
+        (Type::U64, QueryValue::I64(v)) => Term::from_field_u64(
+            field,
+            std::convert::TryFrom::try_from(*v).map_err(|_| mismatch())?,
+        ),
+        (Type::I64, QueryValue::I64(v)) => Term::from_field_i64(field, *v),
+        (Type::I64, QueryValue::U64(v)) => Term::from_field_i64(
+            field,
+            std::convert::TryFrom::try_from(*v).map_err(|_| mismatch())?,
+        ),
+        (Type::F64, QueryValue::F64(v)) => Term::from_field_f64(field, *v),
+        (Type::F64, QueryValue::U64(v)) => Term::from_field_f64(field, *v as f64),
+        (Type::F64, QueryValue::I64(v)) => Term::from_field_f64(field, *v as f64),
+        (Type::Date, QueryValue::Date(v)) => Term::from_field_date(field, v),
+        (Type::HierarchicalFacet, QueryValue::Facet(v)) => {
+            Term::from_facet(field, &v.to_tantivy_facet())
+        }
+        (Type::HierarchicalFacet, QueryValue::Str(v)) => {
+            Term::from_facet(field, &Facet(v.clone()).to_tantivy_facet())
+        }
+        _ => return Err(mismatch()),
+    };
+
+    Ok(term)
+}
@@ -0,0 +1,109 @@
+use tantivy::schema::{FieldEntry, Schema};
+
+/// A difference between the schema of an existing index and the schema it is opened with, see
+/// `err::Error::SchemaMismatch`
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum SchemaDifference {
+    /// A field that is only in the new schema.
+    Added(FieldEntry),
+    /// A field that is only in the existing schema.
+    Removed(FieldEntry),
+    /// A field with a different value type.
+    TypeChanged { existing: FieldEntry, new: FieldEntry },
+    /// A field with the same value type but different options.
+    OptionsChanged { existing: FieldEntry, new: FieldEntry },
+    /// A field at a different position in the schema, only reported if no fields were added or
+    /// removed.
+    Moved { field_name: String, existing: u32, new: u32 },
+}
+
+impl SchemaDifference {
+    /// The name of the field that differs.
+    pub fn field_name(&self) -> &str {
+        match self {
+            SchemaDifference::Added(entry) | SchemaDifference::Removed(entry) => entry.name(),
+            SchemaDifference::TypeChanged { new, .. }
+            | SchemaDifference::OptionsChanged { new, .. } => new.name(),
+            SchemaDifference::Moved { field_name, .. } => field_name,
+        }
+    }
+}
+
+impl std::fmt::Display for SchemaDifference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaDifference::Added(entry) => write!(f, "field `{}` added", entry.name()),
+            SchemaDifference::Removed(entry) => write!(f, "field `{}` removed", entry.name()),
+            SchemaDifference::TypeChanged { existing, new } => write!(
+                f,
+                "field `{}` changed type from {:?} to {:?}",
+                new.name(),
+                existing.field_type().value_type(),
+                new.field_type().value_type()
+            ),
+            SchemaDifference::OptionsChanged { existing, new } => write!(
+                f,
+                "field `{}` changed options from {:?} to {:?}",
+                new.name(),
+                existing.field_type(),
+                new.field_type()
+            ),
+            SchemaDifference::Moved { field_name, existing, new } => {
+                write!(f, "field `{}` moved from position {} to {}", field_name, existing, new)
+            }
+        }
+    }
+}
+
+/// Compare field names, types, options and positions, returns every difference.
+pub(crate) fn schema_differences(existing: &Schema, new: &Schema) -> Vec<SchemaDifference> {
+    let mut differences = existing
+        .fields()
+        .filter(|(_, entry)| new.get_field(entry.name()).is_none())
+        .map(|(_, entry)| SchemaDifference::Removed(entry.clone()))
+        .collect::<Vec<_>>();
+
+    let mut moved = Vec::new();
+
+    for (new_field, new_entry) in new.fields() {
+        let existing_field = match existing.get_field(new_entry.name()) {
+            Some(existing_field) => existing_field,
+            None => {
+                differences.push(SchemaDifference::Added(new_entry.clone()));
+                continue;
+            }
+        };
+
+        let existing_entry = existing.get_field_entry(existing_field);
+
+        if existing_entry.field_type().value_type() != new_entry.field_type().value_type() {
+            differences.push(SchemaDifference::TypeChanged {
+                existing: existing_entry.clone(),
+                new: new_entry.clone(),
+            });
+        } else if existing_entry != new_entry {
+            differences.push(SchemaDifference::OptionsChanged {
+                existing: existing_entry.clone(),
+                new: new_entry.clone(),
+            });
+        }
+
+        if existing_field != new_field {
+            moved.push(SchemaDifference::Moved {
+                field_name: new_entry.name().to_string(),
+                existing: existing_field.field_id(),
+                new: new_field.field_id(),
+            });
+        }
+    }
+
+    // Positions only differ by themselves if the same fields are in both schemas.
+    if !differences
+        .iter()
+        .any(|x| matches!(x, SchemaDifference::Added(_) | SchemaDifference::Removed(_)))
+    {
+        differences.extend(moved);
+    }
+
+    differences
+}
@@ -29,8 +29,8 @@ impl tantivy::collector::Collector for ScoredIds {
         segment: &tantivy::SegmentReader,
     ) -> tantivy::Result<Self::Child> {
         Ok(ScoredIdsSegmentCollector {
-            buffer: self.size_hint.map(Vec::with_capacity).unwrap_or_else(Vec::new),
-            id_field_reader: segment.fast_fields().u64(self.id_field.clone()).ok(),
+            buffer: self.size_hint.map(Vec::with_capacity).unwrap_or_default(),
+            id_field_reader: segment.fast_fields().u64(self.id_field).ok(),
         })
     }
 
@@ -40,7 +40,12 @@ impl tantivy::collector::Collector for ScoredIds {
 
     fn merge_fruits(&self, segment_fruits: Vec<Self::Fruit>) -> tantivy::Result<Self::Fruit> {
         let mut out = segment_fruits.into_iter().flatten().collect::<Vec<_>>();
-        out.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or_else(|| a.id.cmp(&b.id)));
+        out.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.id.cmp(&b.id))
+        });
         Ok(out)
     }
 }
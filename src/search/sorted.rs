@@ -0,0 +1,236 @@
+use crate::search::{AsQuery, Page, Params, Results, ScoredId, Searcher};
+use crate::{err, DocumentLike, Store};
+use std::cmp::Ordering;
+use tantivy::fastfield::{FastFieldReader, FastValue};
+
+/// Sort direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+/// Like `ScoredIds`, but ordered by the values of one or more fast fields
+///
+/// Fields must be single-valued `u64`, `i64`, `f64` or date fields with the `FAST` option. Later
+/// fields are used to break ties in earlier ones, and any remaining ties are ordered by score,
+/// then by `id`.
+pub struct SortedIds {
+    pub id_field: tantivy::schema::Field,
+    pub sort_by: Vec<(tantivy::schema::Field, Order)>,
+}
+
+// Used by the `SortedIds` collector.
+#[doc(hidden)]
+pub struct SortedIdsSegmentCollector {
+    id_field_reader: Option<FastFieldReader<u64>>,
+    sort_field_readers: Vec<SortFieldReader>,
+    buffer: Vec<(Vec<u64>, ScoredId)>,
+}
+
+// Fast field reader for any sortable type, values are mapped to order-preserving `u64`s.
+enum SortFieldReader {
+    U64(FastFieldReader<u64>),
+    I64(FastFieldReader<i64>),
+    F64(FastFieldReader<f64>),
+    Date(FastFieldReader<tantivy::DateTime>),
+}
+
+impl SortFieldReader {
+    fn open(
+        segment: &tantivy::SegmentReader,
+        field: tantivy::schema::Field,
+    ) -> tantivy::Result<Self> {
+        use tantivy::schema::FieldType;
+
+        let fast_fields = segment.fast_fields();
+        let field_entry = segment.schema().get_field_entry(field);
+
+        Ok(match field_entry.field_type() {
+            FieldType::U64(_) => SortFieldReader::U64(fast_fields.u64(field)?),
+            FieldType::I64(_) => SortFieldReader::I64(fast_fields.i64(field)?),
+            FieldType::F64(_) => SortFieldReader::F64(fast_fields.f64(field)?),
+            FieldType::Date(_) => SortFieldReader::Date(fast_fields.date(field)?),
+            _ => {
+                return Err(tantivy::TantivyError::SchemaError(format!(
+                    "Field {:?} cannot be used for sorting",
+                    field_entry.name()
+                )))
+            }
+        })
+    }
+
+    fn get(&self, doc: tantivy::DocId) -> u64 {
+        match self {
+            SortFieldReader::U64(reader) => reader.get(doc),
+            SortFieldReader::I64(reader) => reader.get(doc).to_u64(),
+            SortFieldReader::F64(reader) => reader.get(doc).to_u64(),
+            SortFieldReader::Date(reader) => reader.get(doc).to_u64(),
+        }
+    }
+}
+
+impl SortedIds {
+    fn compare(&self, a: &(Vec<u64>, ScoredId), b: &(Vec<u64>, ScoredId)) -> Ordering {
+        self.sort_by
+            .iter()
+            .zip(a.0.iter().zip(b.0.iter()))
+            .map(|((_, order), (a_key, b_key))| match order {
+                Order::Asc => a_key.cmp(b_key),
+                Order::Desc => b_key.cmp(a_key),
+            })
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or_else(|| {
+                b.1.score
+                    .partial_cmp(&a.1.score)
+                    .unwrap_or(Ordering::Equal)
+                    .then_with(|| a.1.id.cmp(&b.1.id))
+            })
+    }
+}
+
+impl tantivy::collector::Collector for SortedIds {
+    type Fruit = Vec<ScoredId>;
+    type Child = SortedIdsSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: tantivy::SegmentLocalId,
+        segment: &tantivy::SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        let sort_field_readers = self
+            .sort_by
+            .iter()
+            .map(|(field, _)| SortFieldReader::open(segment, *field))
+            .collect::<tantivy::Result<Vec<_>>>()?;
+
+        Ok(SortedIdsSegmentCollector {
+            id_field_reader: segment.fast_fields().u64(self.id_field).ok(),
+            sort_field_readers,
+            buffer: Vec::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<Vec<(Vec<u64>, ScoredId)>>,
+    ) -> tantivy::Result<Self::Fruit> {
+        let mut out = segment_fruits.into_iter().flatten().collect::<Vec<_>>();
+        out.sort_by(|a, b| self.compare(a, b));
+        Ok(out.into_iter().map(|(_, scored_id)| scored_id).collect())
+    }
+}
+
+impl tantivy::collector::SegmentCollector for SortedIdsSegmentCollector {
+    type Fruit = Vec<(Vec<u64>, ScoredId)>;
+
+    fn collect(&mut self, doc: tantivy::DocId, score: tantivy::Score) {
+        if let Some(ref id_field_reader) = self.id_field_reader {
+            let keys = self.sort_field_readers.iter().map(|reader| reader.get(doc)).collect();
+            self.buffer.push((keys, ScoredId { score, id: id_field_reader.get(doc) }));
+        }
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        self.buffer
+    }
+}
+
+/**
+Searcher that orders results by fast field values instead of score
+
+Sort fields are referenced by their index field name, and are applied in the order given.
+Only the requested `Page` of sorted results is loaded.
+
+## Usage:
+
+```rust
+use pallet::{err, search, DocumentLike, Store};
+
+fn top_rated<T>(store: &Store<T>, query: &str) -> err::Result<search::Results<T>>
+where
+    T: DocumentLike + Send,
+    T::IndexFieldsType: Sync,
+{
+    let sorted = search::Sorted::new(query)
+        .with_sort_field("rating", search::Order::Desc)
+        .with_sort_field("year", search::Order::Asc)
+        .with_limit(20);
+
+    store.search(sorted)
+}
+```
+*/
+pub struct Sorted<Q> {
+    pub(crate) query: Q,
+    pub(crate) sort_by: Vec<(String, Order)>,
+    pub(crate) page: Page,
+}
+
+impl<Q> Sorted<Q> {
+    /// Create a new `Sorted` searcher.
+    pub fn new(query: Q) -> Self {
+        Sorted { query, sort_by: Vec::new(), page: Page::default() }
+    }
+
+    /// Add a field to sort by, after any previously added fields.
+    pub fn with_sort_field<I: Into<String>>(mut self, field_name: I, order: Order) -> Self {
+        self.sort_by.push((field_name.into(), order));
+        self
+    }
+
+    /// Set the page of results to return.
+    pub fn with_page(mut self, page: Page) -> Self {
+        self.page = page;
+        self
+    }
+
+    /// Set the number of results to skip.
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.page.offset = offset;
+        self
+    }
+
+    /// Set the maximum number of results to return.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.page.limit = limit;
+        self
+    }
+}
+
+impl<Q, T> Searcher<T> for Sorted<Q>
+where
+    Q: AsQuery,
+    T: DocumentLike + Send,
+    T::IndexFieldsType: Sync,
+{
+    type Item = Results<T>;
+    type Error = err::Error;
+
+    fn search(&self, store: &Store<T>) -> Result<Self::Item, Self::Error> {
+        let sort_by = self
+            .sort_by
+            .iter()
+            .map(|(field_name, order)| Ok((store.index.field(field_name)?, *order)))
+            .collect::<err::Result<Vec<_>>>()?;
+
+        let sorted_ids_handle = SortedIds { id_field: store.index.id_field, sort_by };
+        let count_handle = tantivy::collector::Count;
+
+        let query = self.query.as_query(&store.index.inner, &store.index.default_search_fields)?;
+
+        let search_params = Params::default()
+            .with_query(query)
+            .with_collector((count_handle, sorted_ids_handle))
+            .with_handler(|(count, sorted_ids)| -> Result<_, err::Error> {
+                let hits = self.page.load_hits(store, sorted_ids)?;
+                Ok(Results { count, hits })
+            });
+
+        search_params.search(store)
+    }
+}
@@ -0,0 +1,119 @@
+use crate::err;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use tantivy::schema::{Field, FieldType, IndexRecordOption};
+use tantivy::termdict::TermMerger;
+use tantivy::DocSet;
+
+/// Completion returned by `Store::suggest`, with the number of documents containing it
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Suggestion {
+    pub text: String,
+    pub count: u64,
+}
+
+/// Check that a suggest field can be completed from its term dictionary.
+pub(crate) fn check_suggest_field(
+    schema: &tantivy::schema::Schema,
+    field: Field,
+) -> err::Result<()> {
+    let field_entry = schema.get_field_entry(field);
+
+    match field_entry.field_type() {
+        FieldType::Str(options) if options.get_indexing_options().is_some() => Ok(()),
+        _ => Err(err::custom(format!(
+            "Suggest field `{}` must be an indexed text field",
+            field_entry.name()
+        ))),
+    }
+}
+
+/// Complete the last term of `prefix` from the terms of `field`, most common first.
+///
+/// Terms are read from the term dictionary of each segment. Counts exclude deleted documents, so
+/// their postings are read for segments with deletes, and terms only in deleted documents are not
+/// suggested. Every term starting with the last term of `prefix` is read (only the top `limit`
+/// are kept), so short prefixes of large fields are the most costly.
+pub(crate) fn suggest(
+    index: &tantivy::Index,
+    reader: &tantivy::IndexReader,
+    field: Field,
+    prefix: &str,
+    limit: usize,
+) -> err::Result<Vec<Suggestion>> {
+    let mut last_token = None;
+    index.tokenizer_for_field(field)?.token_stream(prefix).process(&mut |token| {
+        last_token = Some(token.clone());
+    });
+
+    // Only complete a term that is still being typed.
+    let last_token = match last_token {
+        Some(token) if token.offset_to == prefix.len() && limit > 0 => token,
+        _ => return Ok(Vec::new()),
+    };
+
+    let term_prefix = last_token.text.as_bytes();
+
+    let searcher = reader.searcher();
+    let segment_readers = searcher.segment_readers();
+
+    let inverted_indexes = segment_readers
+        .iter()
+        .map(|segment_reader| segment_reader.inverted_index(field))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let streams = inverted_indexes
+        .iter()
+        .map(|inverted_index| inverted_index.terms().range().ge(term_prefix).into_stream())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(tantivy::TantivyError::from)?;
+
+    // Terms in order across all segments, keeping the `limit` most common with the least common
+    // (and last alphabetically) on top.
+    let mut terms = TermMerger::new(streams);
+    let mut top = BinaryHeap::with_capacity(limit + 1);
+
+    while terms.advance() {
+        if !terms.key().starts_with(term_prefix) {
+            break;
+        }
+        let term = match std::str::from_utf8(terms.key()) {
+            Ok(term) => term,
+            Err(_) => continue,
+        };
+
+        let mut count = 0;
+        for x in terms.current_kvs() {
+            let term_info = x.streamer.value();
+            count += match segment_readers[x.segment_ord].delete_bitset() {
+                Some(delete_bitset) => u64::from(
+                    inverted_indexes[x.segment_ord]
+                        .read_postings_from_terminfo(term_info, IndexRecordOption::Basic)
+                        .map_err(tantivy::TantivyError::from)?
+                        .count(delete_bitset),
+                ),
+                None => u64::from(term_info.doc_freq),
+            };
+        }
+
+        if count == 0 {
+            continue;
+        }
+
+        top.push(Reverse((count, Reverse(term.to_string()))));
+        if top.len() > limit {
+            top.pop();
+        }
+    }
+
+    let completed = &prefix[..last_token.offset_from];
+
+    Ok(top
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((count, Reverse(term)))| Suggestion {
+            text: format!("{}{}", completed, term),
+            count,
+        })
+        .collect())
+}
@@ -0,0 +1,106 @@
+use crate::search::{Query, QueryValue};
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
+
+/**
+Index field reference with a known value type, for building a `Query`
+
+When using `pallet_macros`, these are returned by the query helpers generated for each indexed
+field, so field names and value types are checked at compile time.
+
+## Usage:
+
+```rust
+#[derive(serde::Serialize, serde::Deserialize, pallet::DocumentLike)]
+#[pallet(tree_name = "books")]
+pub struct Book {
+    title: String,
+    #[pallet(index_field_type = "u64")]
+    rating: u8,
+}
+
+let query = Book::q().title().contains("old man").must(Book::q().rating().gt(8));
+```
+*/
+#[derive(Debug)]
+pub struct TypedField<V> {
+    field_name: &'static str,
+    marker: PhantomData<fn(V)>,
+}
+
+impl<V> Clone for TypedField<V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<V> Copy for TypedField<V> {}
+
+impl<V> TypedField<V> {
+    /// Create a reference to the index field named `field_name`.
+    pub fn new(field_name: &'static str) -> Self {
+        TypedField { field_name, marker: PhantomData }
+    }
+
+    /// The index field name.
+    pub fn field_name(&self) -> &'static str {
+        self.field_name
+    }
+}
+
+impl<V: Into<QueryValue> + Clone> TypedField<V> {
+    /// Match documents where the field contains `value`, see `Query::term`.
+    pub fn term(self, value: V) -> Query {
+        Query::term(self.field_name, value)
+    }
+
+    /// Match documents where the field has a value in `range`, see `Query::range`.
+    pub fn range<R: RangeBounds<V>>(self, range: R) -> Query {
+        Query::range(self.field_name, range)
+    }
+
+    /// Match documents where the field has a value greater than `value`.
+    pub fn gt(self, value: V) -> Query {
+        self.range((std::ops::Bound::Excluded(value), std::ops::Bound::Unbounded))
+    }
+
+    /// Match documents where the field has a value greater than or equal to `value`.
+    pub fn gte(self, value: V) -> Query {
+        self.range(value..)
+    }
+
+    /// Match documents where the field has a value less than `value`.
+    pub fn lt(self, value: V) -> Query {
+        self.range(..value)
+    }
+
+    /// Match documents where the field has a value less than or equal to `value`.
+    pub fn lte(self, value: V) -> Query {
+        self.range(..=value)
+    }
+}
+
+impl TypedField<String> {
+    /// Match documents where the text field contains every term of `text`, see `Query::term`.
+    pub fn contains<I: Into<String>>(self, text: I) -> Query {
+        Query::term(self.field_name, text.into())
+    }
+
+    /// Match documents where the text field contains the terms of `text` in order, see
+    /// `Query::phrase`.
+    pub fn phrase<I: Into<String>>(self, text: I) -> Query {
+        Query::phrase(self.field_name, text)
+    }
+
+    /// Match documents where the text field contains terms similar to those of `text`, see
+    /// `Query::fuzzy`.
+    pub fn fuzzy<I: Into<String>>(self, text: I, distance: u8) -> Query {
+        Query::fuzzy(self.field_name, text, distance)
+    }
+
+    /// Match documents where the text field contains a term starting with `text`, see
+    /// `Query::prefix`.
+    pub fn prefix<I: Into<String>>(self, text: I) -> Query {
+        Query::prefix(self.field_name, text)
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{db, err, Document, DocumentLike, IndexChange, Store};
+use sled::transaction::ConflictableTransactionResult;
+use sled::Transactional;
+use std::cell::RefCell;
+
+/// Result type for operations within a `Transaction`
+///
+/// Errors abort the transaction, while conflicts with other writes cause it to be retried.
+pub type TransactionResult<T> = ConflictableTransactionResult<T, err::Error>;
+
+// Index changes and pending log entries made by a `TransactionStore`, or by a write to a `Store`.
+pub(crate) type Changes = (Vec<IndexChange>, Vec<(u64, u64)>);
+
+// Type-erased `Store`, for the stores taking part in a `Transaction`.
+pub(crate) trait Participant {
+    fn participant_tree(&self) -> &db::Tree;
+
+    fn apply_changes(&self, changes: Changes) -> err::Result<()>;
+}
+
+impl<T: DocumentLike> Participant for Store<T> {
+    fn participant_tree(&self) -> &db::Tree {
+        &self.tree
+    }
+
+    fn apply_changes(&self, (changes, pending): Changes) -> err::Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+        self.apply_index_changes(changes, &pending).inspect_err(|_| self.mark_for_replay())
+    }
+}
+
+/**
+Transaction spanning several `Store`s that share a `sled::Db`
+
+All tree changes are made in a single `sled` transaction, and the closure may be run more than
+once if it conflicts with other writes. Index changes are only applied to each `Store`'s search
+index once the transaction has committed (and are discarded if it aborts), and are committed
+according to each `Store`'s `CommitPolicy`.
+
+## Usage:
+
+```rust
+use pallet::{err, DocumentLike, Store, Transaction};
+
+fn move_document<A, B>(from: &Store<A>, to: &Store<B>, id: u64, new: &B) -> err::Result<u64>
+where
+    A: DocumentLike,
+    B: DocumentLike,
+{
+    Transaction::new().with_store(from).with_store(to).run(|txn| {
+        txn.store(from)?.delete(id)?;
+        let new_id = txn.store(to)?.create(new)?;
+        Ok(new_id)
+    })
+}
+```
+*/
+#[derive(Default)]
+pub struct Transaction<'a> {
+    stores: Vec<&'a dyn Participant>,
+}
+
+impl<'a> Transaction<'a> {
+    /// Create a new `Transaction`.
+    pub fn new() -> Self {
+        Transaction::default()
+    }
+
+    /// Add a `Store` to take part in the transaction.
+    pub fn with_store<T: DocumentLike>(mut self, store: &'a Store<T>) -> Self {
+        if !self.stores.iter().any(|x| std::ptr::eq(x.participant_tree(), &store.tree)) {
+            self.stores.push(store);
+        }
+        self
+    }
+
+    /// Run the transaction, returns the value returned by `f`.
+    ///
+    /// Fails if the `Store`s do not share a `sled::Db`. Once the transaction has committed, index
+    /// changes are applied to every `Store` even if some fail, in which case the first error is
+    /// returned and the failed changes are replayed by the next write to (or commit of) that
+    /// `Store`.
+    pub fn run<F, R>(&self, f: F) -> err::Result<R>
+    where
+        F: Fn(&TransactionContext<'_>) -> TransactionResult<R>,
+    {
+        let tree_counts = self
+            .stores
+            .iter()
+            .map(|store| store.participant_tree().transaction_trees().len())
+            .collect::<Vec<_>>();
+
+        let trees = self
+            .stores
+            .iter()
+            .flat_map(|store| store.participant_tree().transaction_trees())
+            .collect::<Vec<_>>();
+
+        let (out, changes) = trees.as_slice().transaction(|view| {
+            let mut offset = 0;
+            let views = self
+                .stores
+                .iter()
+                .zip(&tree_counts)
+                .map(|(store, count)| {
+                    let store_view = &view[offset..offset + count];
+                    offset += count;
+                    store.participant_tree().transaction_view(store_view)
+                })
+                .collect();
+
+            let context = TransactionContext {
+                stores: &self.stores,
+                views,
+                changes: self.stores.iter().map(|_| RefCell::default()).collect(),
+            };
+
+            let out = f(&context)?;
+
+            Ok((out, context.changes.into_iter().map(RefCell::into_inner).collect::<Vec<_>>()))
+        })?;
+
+        let mut first_err = None;
+
+        for (store, changes) in self.stores.iter().zip(changes) {
+            if let Err(e) = store.apply_changes(changes) {
+                first_err.get_or_insert(e);
+            }
+        }
+
+        match first_err {
+            Some(e) => Err(e),
+            None => Ok(out),
+        }
+    }
+}
+
+/// The state of a running `Transaction`, passed to `Transaction::run`
+pub struct TransactionContext<'t> {
+    stores: &'t [&'t dyn Participant],
+    views: Vec<db::Transaction<'t>>,
+    changes: Vec<RefCell<Changes>>,
+}
+
+impl<'t> TransactionContext<'t> {
+    /// Get a handle to make changes to a `Store` within the transaction.
+    ///
+    /// Fails if the `Store` was not added with `Transaction::with_store`.
+    pub fn store<'s, T: DocumentLike>(
+        &'s self,
+        store: &'s Store<T>,
+    ) -> err::Result<TransactionStore<'s, T>> {
+        let idx = self
+            .stores
+            .iter()
+            .position(|x| std::ptr::eq(x.participant_tree(), &store.tree))
+            .ok_or_else(|| err::custom("`Store` is not part of this transaction"))?;
+
+        Ok(TransactionStore { store, txn: &self.views[idx], changes: &self.changes[idx] })
+    }
+}
+
+/// Handle to make changes to a `Store` within a `Transaction`
+///
+/// Does not include `search`, as changes are not visible in the search index until the
+/// `Transaction` has committed.
+pub struct TransactionStore<'s, T: DocumentLike> {
+    store: &'s Store<T>,
+    txn: &'s db::Transaction<'s>,
+    changes: &'s RefCell<Changes>,
+}
+
+impl<T: DocumentLike> TransactionStore<'_, T> {
+    /// Create a new `Document`, returns the `id`.
+    pub fn create(&self, inner: &T) -> TransactionResult<u64> {
+        let prepared = self.store.prepare(inner)?;
+        let (id, entry) = self.store.txn_create(self.txn, None, &prepared)?;
+        self.push(IndexChange::Upsert(id, prepared), entry);
+        Ok(id)
+    }
+
+    /// Create a new `Document` with a caller-supplied `id`, see `Store::insert_with_id`.
+    pub fn insert_with_id(&self, id: u64, inner: &T) -> TransactionResult<()> {
+        let prepared = self.store.prepare(inner)?;
+        let (id, entry) = self.store.txn_create(self.txn, Some(id), &prepared)?;
+        self.push(IndexChange::Upsert(id, prepared), entry);
+        Ok(())
+    }
+
+    /// Update a `Document`, returns the new version, see `Store::update`.
+    pub fn update(&self, doc: &Document<T>) -> TransactionResult<u64> {
+        let prepared = self.store.prepare(&doc.inner)?;
+        let (version, entry) = self.store.txn_update(self.txn, doc.id, doc.version(), &prepared)?;
+        self.push(IndexChange::Upsert(doc.id, prepared), entry);
+        Ok(version)
+    }
+
+    /// Update a `Document` in place, see `Store::update_with`.
+    pub fn update_with<F>(&self, id: u64, mut f: F) -> TransactionResult<Option<Document<T>>>
+    where
+        F: FnMut(&mut T),
+    {
+        match self.store.txn_update_with(self.txn, id, &mut f)? {
+            Some((doc, prepared, entry)) => {
+                self.push(IndexChange::Upsert(id, prepared), entry);
+                Ok(Some(doc))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Delete a `Document` by `id`.
+    pub fn delete(&self, id: u64) -> TransactionResult<()> {
+        let entry = self.store.txn_delete(self.txn, id)?;
+        self.push(IndexChange::Refresh(id), entry);
+        Ok(())
+    }
+
+    /// Find a single `Document` by its `id`, including changes made in this transaction.
+    pub fn find(&self, id: u64) -> TransactionResult<Option<Document<T>>> {
+        let value = match self.txn.tree.get(db::id_key(id))? {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+
+        let version = self.txn.versions.get(db::id_key(id))?.map(|v| db::key_id(&v)).transpose()?;
+
+        Ok(Some(Document {
+            id,
+            inner: crate::serialize::deserialize(&value)?,
+            version: Some(version.unwrap_or(0)),
+        }))
+    }
+
+    fn push(&self, change: IndexChange, entry: (u64, u64)) {
+        let mut changes = self.changes.borrow_mut();
+        changes.0.push(change);
+        changes.1.push(entry);
+    }
+}
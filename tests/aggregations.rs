@@ -0,0 +1,132 @@
+mod common;
+
+use common::{open, temp_dir};
+use pallet::search::{
+    Aggregated, AggregationResult, DateHistogram, DateHistogramBucket, DateInterval, FieldStats,
+    Histogram, HistogramBucket, Stats, TermKey, Terms, TermsBucket,
+};
+use tantivy::chrono::{TimeZone, Utc};
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, pallet::DocumentLike)]
+#[pallet(tree_name = "readings")]
+pub struct Reading {
+    #[pallet(default_search_field)]
+    title: String,
+    #[pallet(index_field_options = "tantivy::schema::INDEXED | tantivy::schema::FAST")]
+    score: u64,
+    #[pallet(index_field_options = "tantivy::schema::INDEXED | tantivy::schema::FAST")]
+    tags: Vec<u64>,
+    #[pallet(
+        index_field_type = "tantivy::DateTime",
+        index_field_options = "tantivy::schema::INDEXED | tantivy::schema::FAST"
+    )]
+    taken: Timestamp,
+}
+
+// Unix timestamp in seconds, indexed as a date.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
+pub struct Timestamp(i64);
+
+impl From<Timestamp> for tantivy::DateTime {
+    fn from(timestamp: Timestamp) -> Self {
+        Utc.timestamp_opt(timestamp.0, 0).unwrap()
+    }
+}
+
+fn reading(title: &str, score: u64, tags: &[u64], (year, month, day): (i32, u32, u32)) -> Reading {
+    let taken = Timestamp(Utc.with_ymd_and_hms(year, month, day, 12, 0, 0).unwrap().timestamp());
+    Reading { title: title.into(), score, tags: tags.to_vec(), taken }
+}
+
+fn aggregate(query: &str) -> std::collections::BTreeMap<String, AggregationResult> {
+    let dir = temp_dir();
+    let store = open::<Reading>(dir.path());
+
+    store
+        .create_multi(&[
+            reading("north", 1, &[1, 2], (2021, 1, 10)),
+            reading("north", 3, &[2], (2021, 1, 20)),
+            reading("south", 8, &[2, 3, 3], (2021, 3, 5)),
+        ])
+        .unwrap();
+
+    let aggregated = Aggregated::new(query)
+        .with_aggregation("score", Stats::new("score"))
+        .with_aggregation("score_histogram", Histogram::new("score", 5.0))
+        .with_aggregation("tags", Terms::new("tags", 2))
+        .with_aggregation("tag_stats", Stats::new("tags"))
+        .with_aggregation("monthly", DateHistogram::new("taken", DateInterval::Month))
+        .with_limit(1);
+
+    let results = store.search(aggregated).unwrap();
+    assert_eq!(results.results.hits.len(), 1);
+    results.aggregations
+}
+
+#[test]
+fn aggregates_single_and_multi_valued_fields() {
+    let aggregations = aggregate("north OR south");
+
+    assert_eq!(
+        aggregations["score"],
+        AggregationResult::Stats(FieldStats {
+            count: 3,
+            min: Some(1.0),
+            max: Some(8.0),
+            sum: 12.0,
+            avg: Some(4.0),
+        })
+    );
+
+    assert_eq!(
+        aggregations["score_histogram"],
+        AggregationResult::Histogram(vec![
+            HistogramBucket { key: 0.0, count: 2 },
+            HistogramBucket { key: 5.0, count: 1 },
+        ])
+    );
+
+    // Every value of a multi-valued field is counted.
+    assert_eq!(
+        aggregations["tags"],
+        AggregationResult::Terms(vec![
+            TermsBucket { key: TermKey::U64(2), count: 3 },
+            TermsBucket { key: TermKey::U64(3), count: 2 },
+        ])
+    );
+
+    match &aggregations["tag_stats"] {
+        AggregationResult::Stats(stats) => assert_eq!((stats.count, stats.sum), (6, 13.0)),
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    let month = |year, month| Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).unwrap();
+    assert_eq!(
+        aggregations["monthly"],
+        AggregationResult::DateHistogram(vec![
+            DateHistogramBucket {
+                key: month(2021, 1).timestamp(),
+                key_as_string: month(2021, 1).to_rfc3339(),
+                count: 2,
+            },
+            DateHistogramBucket {
+                key: month(2021, 3).timestamp(),
+                key_as_string: month(2021, 3).to_rfc3339(),
+                count: 1,
+            },
+        ])
+    );
+}
+
+#[test]
+fn aggregates_only_matching_documents() {
+    let aggregations = aggregate("south");
+
+    assert_eq!(
+        aggregations["tags"],
+        AggregationResult::Terms(vec![
+            TermsBucket { key: TermKey::U64(3), count: 2 },
+            TermsBucket { key: TermKey::U64(2), count: 1 },
+        ])
+    );
+}
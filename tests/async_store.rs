@@ -0,0 +1,76 @@
+#![cfg(feature = "async")]
+
+mod common;
+
+use common::{builder, open, temp_dir, Flaky, Note, FAIL};
+use futures_executor::block_on;
+use pallet::{err, AsyncStore, CommitPolicy};
+use std::sync::atomic::Ordering;
+
+#[test]
+fn queued_writes_are_batched() {
+    let dir = temp_dir();
+    let store = AsyncStore::new(open::<Note>(dir.path()));
+
+    let creates = (0..20).map(|n| store.create(Note::new("queued", n))).collect::<Vec<_>>();
+
+    let ids = creates.into_iter().map(|create| block_on(create).unwrap()).collect::<Vec<_>>();
+
+    let all = block_on(store.all()).unwrap();
+
+    assert_eq!(all.iter().map(|doc| doc.id).collect::<Vec<_>>(), ids);
+    assert_eq!(block_on(store.search("queued")).unwrap().count, 20);
+}
+
+#[test]
+fn index_errors_after_commit_are_not_retried() {
+    let dir = temp_dir();
+    let store =
+        builder::<Flaky>(dir.path()).with_commit_policy(CommitPolicy::Manual).finish().unwrap();
+
+    store.create(&Flaky { title: "flaky".into() }).unwrap();
+
+    // Leaves the rolled back change to be replayed by the next write, which then fails.
+    FAIL.store(true, Ordering::SeqCst);
+    assert!(store.repair().is_err());
+
+    let store = AsyncStore::new(store);
+
+    let creates =
+        (0..20).map(|n| store.create(Flaky { title: format!("queued {}", n) })).collect::<Vec<_>>();
+
+    // Errors shared by a batch keep the original error.
+    for create in creates {
+        let e = match block_on(create) {
+            Err(err::Error::Shared(e)) => e,
+            other => panic!("expected a shared error, got {:?}", other.map(|_| ())),
+        };
+        assert!(matches!(*e, err::Error::Custom(ref message) if &**message == "failed to convert"));
+    }
+
+    FAIL.store(false, Ordering::SeqCst);
+
+    // Each create was written once, even if it failed as part of a batch.
+    assert_eq!(block_on(store.all()).unwrap().len(), 21);
+}
+
+#[test]
+fn update_with_is_queued_with_other_writes() {
+    let dir = temp_dir();
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+    let store = AsyncStore::with_thread_pool(open::<Note>(dir.path()), pool);
+
+    let id = block_on(store.create(Note::new("counter", 0))).unwrap();
+
+    let updates = (0..10).map(|_| store.update_with(id, |note| note.n += 1)).collect::<Vec<_>>();
+    let delete = store.delete(id);
+
+    for (n, update) in updates.into_iter().enumerate() {
+        assert_eq!(block_on(update).unwrap().unwrap().n, n as u64 + 1);
+    }
+    block_on(delete).unwrap();
+
+    // Applied in the order queued, so the delete comes after every update.
+    assert!(block_on(store.find(id)).unwrap().is_none());
+    assert!(block_on(store.update_with(id, |note| note.n += 1)).unwrap().is_none());
+}
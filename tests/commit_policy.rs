@@ -0,0 +1,108 @@
+mod common;
+
+use common::{builder, open, open_db, search_ids, temp_dir, Note};
+use pallet::{CommitPolicy, DocumentLike, Store};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn open_with(dir: &std::path::Path, commit_policy: CommitPolicy) -> Store<Note> {
+    builder(dir).with_commit_policy(commit_policy).finish().unwrap()
+}
+
+#[test]
+fn every_write_commits_each_write() {
+    let dir = temp_dir();
+    let store = open::<Note>(dir.path());
+
+    let id = store.create(&Note::new("alpha", 1)).unwrap();
+
+    assert_eq!(search_ids(&store, "alpha"), vec![id]);
+}
+
+#[test]
+fn operations_commits_once_enough_changes() {
+    let dir = temp_dir();
+    let store = open_with(dir.path(), CommitPolicy::Operations(3));
+
+    let ids = store.create_multi(&[Note::new("alpha", 1), Note::new("alpha", 2)]).unwrap();
+
+    assert!(search_ids(&store, "alpha").is_empty());
+    assert_eq!(store.find(ids[0]).unwrap().unwrap().n, 1);
+
+    let id = store.create(&Note::new("alpha", 3)).unwrap();
+
+    assert_eq!(search_ids(&store, "alpha"), vec![ids[0], ids[1], id]);
+}
+
+#[test]
+fn interval_commits_on_first_write_after_interval() {
+    let dir = temp_dir();
+    let store = open_with(dir.path(), CommitPolicy::Interval(Duration::from_millis(200)));
+
+    let first = store.create(&Note::new("alpha", 1)).unwrap();
+
+    assert!(search_ids(&store, "alpha").is_empty());
+
+    std::thread::sleep(Duration::from_millis(250));
+
+    // Still not committed, as there is no background timer.
+    assert!(search_ids(&store, "alpha").is_empty());
+
+    let second = store.create(&Note::new("alpha", 2)).unwrap();
+
+    assert_eq!(search_ids(&store, "alpha"), vec![first, second]);
+}
+
+#[test]
+fn manual_commits_on_commit_only() {
+    let dir = temp_dir();
+
+    let (first, second) = {
+        let store = open_with(dir.path(), CommitPolicy::Manual);
+
+        let first = store.create(&Note::new("alpha", 1)).unwrap();
+
+        assert!(search_ids(&store, "alpha").is_empty());
+
+        store.commit().unwrap();
+
+        assert_eq!(search_ids(&store, "alpha"), vec![first]);
+
+        let second = store.create(&Note::new("alpha", 2)).unwrap();
+
+        (first, second)
+    };
+
+    // Uncommitted changes are replayed when opened again.
+    let store = open_with(dir.path(), CommitPolicy::Manual);
+
+    assert_eq!(search_ids(&store, "alpha"), vec![first, second]);
+}
+
+#[test]
+fn committing_without_changes_does_not_create_a_writer() {
+    let dir = temp_dir();
+    let writers = Arc::new(AtomicUsize::new(0));
+
+    let index_builder = Note::index_builder().with_writer_accessor({
+        let writers = writers.clone();
+        move |index| {
+            writers.fetch_add(1, Ordering::SeqCst);
+            index.writer(50_000_000)
+        }
+    });
+
+    let store: Store<Note> = Store::builder()
+        .with_index_builder(index_builder)
+        .with_db(open_db(dir.path()))
+        .with_index_dir(dir.path())
+        .finish()
+        .unwrap();
+    let opened = writers.load(Ordering::SeqCst);
+
+    store.commit().unwrap();
+    drop(store);
+
+    assert_eq!(writers.load(Ordering::SeqCst), opened);
+}
@@ -0,0 +1,89 @@
+#![allow(dead_code)]
+
+use pallet::{db, err, search, DocumentLike, Store};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, pallet::DocumentLike)]
+#[pallet(tree_name = "notes")]
+pub struct Note {
+    #[pallet(default_search_field)]
+    pub title: String,
+    #[pallet(index_field_type = "u64")]
+    pub n: u64,
+}
+
+impl Note {
+    pub fn new(title: &str, n: u64) -> Self {
+        Note { title: title.into(), n }
+    }
+}
+
+// Makes `Flaky::as_index_document` fail while set, for titles starting with `flaky`.
+pub static FAIL: AtomicBool = AtomicBool::new(false);
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct Flaky {
+    pub title: String,
+}
+
+impl DocumentLike for Flaky {
+    type IndexFieldsType = tantivy::schema::Field;
+
+    fn as_index_document(&self, title: &tantivy::schema::Field) -> err::Result<tantivy::Document> {
+        if FAIL.load(Ordering::SeqCst) && self.title.starts_with("flaky") {
+            return Err(err::custom("failed to convert"));
+        }
+        let mut doc = tantivy::Document::new();
+        doc.add_text(*title, &self.title);
+        Ok(doc)
+    }
+
+    fn tree_builder() -> db::TreeBuilder {
+        db::TreeBuilder::default().with_tree_name("flaky")
+    }
+
+    fn index_builder() -> search::IndexBuilder<tantivy::schema::Field> {
+        search::IndexBuilder::default()
+            .with_fields_builder(|schema| Ok(schema.add_text_field("title", tantivy::schema::TEXT)))
+            .with_default_search_fields_builder(|title| vec![*title])
+    }
+}
+
+pub fn temp_dir() -> tempfile::TempDir {
+    tempfile::TempDir::new_in(env!("CARGO_TARGET_TMPDIR")).unwrap()
+}
+
+/// Open the `sled::Db` in `dir`, waiting for the lock if a dropped `Db`'s flusher thread still holds
+/// it.
+pub fn open_db(dir: &Path) -> sled::Db {
+    for _ in 0..50 {
+        match sled::open(dir.join("db")) {
+            Err(sled::Error::Io(e)) if e.kind() == std::io::ErrorKind::Other => {
+                std::thread::sleep(std::time::Duration::from_millis(20));
+            }
+            res => return res.unwrap(),
+        }
+    }
+    sled::open(dir.join("db")).unwrap()
+}
+
+pub fn builder<T: DocumentLike>(dir: &Path) -> pallet::StoreBuilder<T> {
+    Store::builder().with_db(open_db(dir)).with_index_dir(dir)
+}
+
+pub fn open<T: DocumentLike>(dir: &Path) -> Store<T> {
+    builder(dir).finish().unwrap()
+}
+
+/// The `id`s of the `Document`s matching a query, in `id` order.
+pub fn search_ids<T>(store: &Store<T>, query: &str) -> Vec<u64>
+where
+    T: DocumentLike + Send,
+    T::IndexFieldsType: Sync,
+{
+    let mut ids =
+        store.search(query).unwrap().hits.into_iter().map(|hit| hit.doc.id).collect::<Vec<_>>();
+    ids.sort_unstable();
+    ids
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::{builder, temp_dir};
+use pallet::search::{FieldValue, Query};
+use tantivy::schema::TextOptions;
+
+// Generic over the type of an indexed field.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, pallet::DocumentLike)]
+#[serde(bound = "")]
+#[pallet(tree_name = "labelled")]
+pub struct Labelled<T>
+where
+    T: FieldValue<FieldOptionsType = TextOptions, Item = String>
+        + serde::Serialize
+        + serde::de::DeserializeOwned,
+{
+    #[pallet(default_search_field, index_field_options = "tantivy::schema::TEXT")]
+    label: T,
+    #[pallet(index_field_type = "u64")]
+    n: u64,
+}
+
+#[test]
+fn derives_query_helpers_for_generic_structs() {
+    let dir = temp_dir();
+    let store = builder::<Labelled<String>>(dir.path()).finish().unwrap();
+
+    store.create(&Labelled { label: "alpha".to_string(), n: 1 }).unwrap();
+    store.create(&Labelled { label: "beta".to_string(), n: 2 }).unwrap();
+
+    let q = Labelled::<String>::q();
+    let query = Query::boolean().should(q.label().term("alpha".into())).should(q.n().gt(1));
+
+    let mut ns = store.search(&query).unwrap().hits.iter().map(|hit| hit.doc.n).collect::<Vec<_>>();
+    ns.sort_unstable();
+    assert_eq!(ns, vec![1, 2]);
+
+    assert_eq!(format!("{:?}", q), "LabelledQuery");
+}
+
+// Already using the default names of the generated structs.
+pub struct TaggedFields;
+pub struct TaggedQuery;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, pallet::DocumentLike)]
+#[pallet(tree_name = "tagged", fields_struct = "TaggedIndexFields", query_struct = "TaggedQ")]
+pub struct Tagged {
+    #[pallet(default_search_field)]
+    tag: String,
+}
+
+#[test]
+fn renames_generated_structs() {
+    let dir = temp_dir();
+    let store = builder::<Tagged>(dir.path()).finish().unwrap();
+
+    store.create(&Tagged { tag: "alpha".to_string() }).unwrap();
+
+    let fields: &TaggedIndexFields = &store.index.fields;
+    assert_ne!(fields.tag, store.index.id_field);
+
+    let q: TaggedQ = Tagged::q();
+    assert_eq!(store.search(&q.tag().contains("alpha")).unwrap().hits.len(), 1);
+
+    let _ = (TaggedFields, TaggedQuery);
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::{open, temp_dir};
+use pallet::search::{FacetCount, Faceted};
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, pallet::DocumentLike)]
+#[pallet(tree_name = "books")]
+pub struct Book {
+    #[pallet(default_search_field)]
+    title: String,
+    #[pallet(facet)]
+    category: String,
+    #[pallet(facet)]
+    series: Option<String>,
+    #[pallet(facet)]
+    tags: Vec<String>,
+}
+
+fn book(title: &str, category: &str, series: Option<&str>, tags: &[&str]) -> Book {
+    Book {
+        title: title.into(),
+        category: category.into(),
+        series: series.map(Into::into),
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+    }
+}
+
+fn counts(facet_counts: Vec<FacetCount>) -> Vec<(String, u64)> {
+    facet_counts.into_iter().map(|FacetCount { facet, count }| (facet, count)).collect()
+}
+
+#[test]
+fn counts_single_optional_and_multi_valued_facets() {
+    let dir = temp_dir();
+    let store = open::<Book>(dir.path());
+
+    store
+        .create_multi(&[
+            book("dune", "/fiction/scifi", Some("/dune"), &["/classic", "/desert"]),
+            book("dune messiah", "/fiction/scifi", Some("/dune"), &["/desert"]),
+            book("emma", "/fiction/romance", None, &["/classic"]),
+            book("cosmos", "/science", None, &[]),
+            book("other", "/science", None, &[]),
+        ])
+        .unwrap();
+
+    let results = store
+        .search(Faceted::new("dune OR emma OR cosmos", "category").with_facet("/fiction"))
+        .unwrap();
+    assert_eq!(results.results.count, 4);
+    assert_eq!(
+        counts(results.facet_counts),
+        vec![("/fiction/romance".into(), 1), ("/fiction/scifi".into(), 2)]
+    );
+
+    // Without a requested facet, the root's children are counted.
+    let results = store.search(Faceted::new("dune OR emma OR cosmos", "category")).unwrap();
+    assert_eq!(counts(results.facet_counts), vec![("/fiction".into(), 3), ("/science".into(), 1)]);
+
+    let results = store.search(Faceted::new("dune OR emma OR cosmos", "series")).unwrap();
+    assert_eq!(counts(results.facet_counts), vec![("/dune".into(), 2)]);
+
+    // Facet counts cover every match, not just the returned page.
+    let results = store.search(Faceted::new("dune OR emma", "tags").with_limit(1)).unwrap();
+    assert_eq!(results.results.hits.len(), 1);
+    assert_eq!(counts(results.facet_counts), vec![("/classic".into(), 2), ("/desert".into(), 2)]);
+
+    let overlapping =
+        Faceted::new("dune", "category").with_facet("/fiction").with_facet("/fiction/scifi");
+    assert!(store.search(overlapping).is_err());
+    assert!(store.search(Faceted::new("dune", "missing")).is_err());
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use common::{open, temp_dir, Note};
+use pallet::search::{Fuzzy, Paged};
+use pallet::Store;
+
+fn fuzzy_ns(store: &Store<Note>, fuzzy: Fuzzy) -> Vec<u64> {
+    store.search(fuzzy).unwrap().hits.into_iter().map(|hit| hit.doc.inner.n).collect()
+}
+
+fn sorted(mut ns: Vec<u64>) -> Vec<u64> {
+    ns.sort_unstable();
+    ns
+}
+
+fn create_notes(store: &Store<Note>) {
+    store
+        .create_multi(&[
+            Note::new("ocean currents", 1),
+            Note::new("the world", 2),
+            Note::new("they would", 3),
+            Note::new("sea breeze", 4),
+        ])
+        .unwrap();
+}
+
+#[test]
+fn allowed_edits_depend_on_term_length() {
+    let dir = temp_dir();
+    let store = open::<Note>(dir.path());
+    create_notes(&store);
+
+    // One edit for a 5 character term, where a transposition counts as 2.
+    assert_eq!(fuzzy_ns(&store, Fuzzy::new("oceen")), vec![1]);
+    assert_eq!(fuzzy_ns(&store, Fuzzy::new("ocaen")), Vec::<u64>::new());
+    assert_eq!(fuzzy_ns(&store, Fuzzy::new("ocaen").with_distance(5, 2)), vec![1]);
+
+    // Shorter terms must match exactly.
+    assert_eq!(fuzzy_ns(&store, Fuzzy::new("sae")), Vec::<u64>::new());
+    assert_eq!(fuzzy_ns(&store, Fuzzy::new("sea")), vec![4]);
+
+    // Two edits for a 9 character term.
+    assert_eq!(fuzzy_ns(&store, Fuzzy::new("curremmts")), vec![1]);
+    assert_eq!(fuzzy_ns(&store, Fuzzy::new("curremmts").with_distance(9, 1)), Vec::<u64>::new());
+    assert_eq!(fuzzy_ns(&store, Fuzzy::new("oceen").with_distance(5, 0)), Vec::<u64>::new());
+}
+
+#[test]
+fn exact_matches_score_higher() {
+    let dir = temp_dir();
+    let store = open::<Note>(dir.path());
+    create_notes(&store);
+
+    assert_eq!(fuzzy_ns(&store, Fuzzy::new("world")), vec![2, 3]);
+    assert_eq!(fuzzy_ns(&store, Fuzzy::new("would")), vec![3, 2]);
+}
+
+#[test]
+fn prefix_and_conjunction() {
+    let dir = temp_dir();
+    let store = open::<Note>(dir.path());
+    create_notes(&store);
+
+    assert_eq!(fuzzy_ns(&store, Fuzzy::new("oce")), Vec::<u64>::new());
+    assert_eq!(fuzzy_ns(&store, Fuzzy::new("oce").with_prefix(true)), vec![1]);
+
+    // Only the last term is a prefix.
+    let fuzzy = Fuzzy::new("oce bre").with_prefix(true);
+    assert_eq!(sorted(fuzzy_ns(&store, fuzzy)), vec![4]);
+
+    assert_eq!(sorted(fuzzy_ns(&store, Fuzzy::new("oceen breeze"))), vec![1, 4]);
+    let fuzzy = Fuzzy::new("oceen breeze").with_conjunction(true);
+    assert_eq!(fuzzy_ns(&store, fuzzy), Vec::<u64>::new());
+    let fuzzy = Fuzzy::new("oceen curents").with_conjunction(true);
+    assert_eq!(fuzzy_ns(&store, fuzzy), vec![1]);
+
+    let paged = store.search(Paged::new(Fuzzy::new("world")).with_limit(1)).unwrap();
+    assert_eq!(paged.count, 2);
+    assert_eq!(paged.hits.len(), 1);
+}
+
+#[test]
+fn invalid_fields_and_distances_are_errors() {
+    let dir = temp_dir();
+    let store = open::<Note>(dir.path());
+    create_notes(&store);
+
+    assert!(store.search(Fuzzy::new("ocean").with_field("missing")).is_err());
+    assert!(store.search(Fuzzy::new("ocean").with_distance(5, 3)).is_err());
+
+    // Non-text fields are skipped.
+    assert_eq!(fuzzy_ns(&store, Fuzzy::new("ocean").with_field("n")), Vec::<u64>::new());
+    assert_eq!(fuzzy_ns(&store, Fuzzy::new("ocean").with_field("title")), vec![1]);
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use common::{open, temp_dir};
+use pallet::search::Highlighted;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, pallet::DocumentLike)]
+#[pallet(tree_name = "articles")]
+pub struct Article {
+    #[pallet(default_search_field)]
+    title: String,
+    #[pallet(default_search_field)]
+    body: String,
+}
+
+fn article(title: &str, body: &str) -> Article {
+    Article { title: title.into(), body: body.into() }
+}
+
+#[test]
+fn highlights_matching_fields_of_each_hit() {
+    let dir = temp_dir();
+    let store = open::<Article>(dir.path());
+
+    store
+        .create_multi(&[
+            article("Sailing", "The old boat left the harbour at dawn"),
+            article("Boat repairs", "Fixing the hull before winter."),
+            article("Gardening", "Nothing about the sea here."),
+        ])
+        .unwrap();
+
+    let mut results = store.search(Highlighted::new("boat")).unwrap();
+    assert_eq!(results.count, 2);
+    results.hits.sort_by_key(|hit| hit.doc.id);
+
+    // Fields without matches are omitted.
+    let sailing = &results.hits[0].snippets;
+    assert_eq!(sailing.keys().collect::<Vec<_>>(), vec!["body"]);
+    assert_eq!(sailing["body"].fragment, "The old boat left the harbour at dawn");
+    assert_eq!(sailing["body"].highlighted, vec![(8, 12)]);
+    assert_eq!(sailing["body"].html, "The old <b>boat</b> left the harbour at dawn");
+
+    let repairs = &results.hits[1].snippets;
+    assert_eq!(repairs.keys().collect::<Vec<_>>(), vec!["title"]);
+    assert_eq!(repairs["title"].html, "<b>Boat</b> repairs");
+
+    // Only the requested fields are highlighted, and fragments are shortened.
+    let results = store
+        .search(
+            Highlighted::new("harbour")
+                .with_field("title")
+                .with_field("body")
+                .with_max_num_chars(20),
+        )
+        .unwrap();
+    assert_eq!(results.hits.len(), 1);
+    let snippet = &results.hits[0].snippets["body"];
+    assert!(snippet.fragment.len() <= 20);
+    assert!(snippet.html.contains("<b>harbour</b>"));
+
+    let results = store.search(Highlighted::new("boat").with_field("title")).unwrap();
+    assert!(results.hits.iter().all(|hit| !hit.snippets.contains_key("body")));
+
+    assert!(store.search(Highlighted::new("boat").with_field("missing")).is_err());
+}
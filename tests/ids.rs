@@ -0,0 +1,92 @@
+mod common;
+
+use common::{temp_dir, Note};
+use pallet::db::{IdStrategy, TreeBuilder};
+use pallet::{err, Store};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+fn open_with(dir: &Path, id_strategy: IdStrategy) -> Store<Note> {
+    let db = common::open_db(dir);
+    Store::builder()
+        .with_tree_builder(TreeBuilder::default().with_db(db).with_id_strategy(id_strategy))
+        .with_index_dir(dir)
+        .finish()
+        .unwrap()
+}
+
+#[test]
+fn sled_ids_skip_caller_supplied_ids() {
+    let dir = temp_dir();
+    let store = open_with(dir.path(), IdStrategy::Sled);
+
+    let first = store.create(&Note::new("alpha", 1)).unwrap();
+
+    // Every other `id` ahead of the next generated ones.
+    let supplied = (1..=50).map(|x| first + x * 2).collect::<Vec<_>>();
+
+    for id in &supplied {
+        store.insert_with_id(*id, &Note::new("beta", *id)).unwrap();
+    }
+
+    for n in 0..20 {
+        let id = store.create(&Note::new("gamma", n)).unwrap();
+        assert!(!supplied.contains(&id));
+    }
+
+    assert_eq!(store.all().unwrap().len(), 71);
+}
+
+#[test]
+fn monotonic_ids_follow_caller_supplied_ids() {
+    let dir = temp_dir();
+
+    {
+        let store = open_with(dir.path(), IdStrategy::Monotonic);
+
+        assert_eq!(store.create(&Note::new("alpha", 1)).unwrap(), 0);
+        store.insert_with_id(10, &Note::new("beta", 2)).unwrap();
+        assert_eq!(store.create(&Note::new("gamma", 3)).unwrap(), 11);
+    }
+
+    let store = open_with(dir.path(), IdStrategy::Monotonic);
+
+    assert_eq!(store.create(&Note::new("delta", 4)).unwrap(), 12);
+}
+
+#[test]
+fn custom_ids_in_use_fail() {
+    let dir = temp_dir();
+
+    let counter = Arc::new(AtomicU64::new(100));
+    let next_id = counter.clone();
+    let store =
+        open_with(dir.path(), IdStrategy::custom(move || next_id.fetch_add(1, Ordering::SeqCst)));
+
+    assert_eq!(store.create(&Note::new("alpha", 1)).unwrap(), 100);
+
+    store.insert_with_id(101, &Note::new("beta", 2)).unwrap();
+
+    match store.create(&Note::new("gamma", 3)) {
+        Err(err::Error::AlreadyExists { id: 101 }) => {}
+        other => panic!("expected `AlreadyExists`, got {:?}", other),
+    }
+
+    assert_eq!(store.create(&Note::new("gamma", 3)).unwrap(), 102);
+}
+
+#[test]
+fn insert_with_id_in_use_fails() {
+    let dir = temp_dir();
+    let store = open_with(dir.path(), IdStrategy::Sled);
+
+    let id = store.create(&Note::new("alpha", 1)).unwrap();
+
+    match store.insert_with_id(id, &Note::new("beta", 2)) {
+        Err(err::Error::AlreadyExists { id: existing }) => assert_eq!(existing, id),
+        other => panic!("expected `AlreadyExists`, got {:?}", other),
+    }
+
+    assert_eq!(store.find(id).unwrap().unwrap().title, "alpha");
+}
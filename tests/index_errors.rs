@@ -0,0 +1,42 @@
+mod common;
+
+use common::{builder, search_ids, temp_dir, Flaky, FAIL};
+use pallet::CommitPolicy;
+use std::sync::atomic::Ordering;
+
+#[test]
+fn failed_index_changes_are_replayed() {
+    let dir = temp_dir();
+
+    let id = {
+        let store =
+            builder::<Flaky>(dir.path()).with_commit_policy(CommitPolicy::Manual).finish().unwrap();
+
+        let first = store.create(&Flaky { title: "flaky first".into() }).unwrap();
+
+        // Fails while re-indexing `first` from the tree, after it was applied to the writer.
+        FAIL.store(true, Ordering::SeqCst);
+        assert!(store.repair().is_err());
+        FAIL.store(false, Ordering::SeqCst);
+
+        // The rolled back change is replayed, rather than its log entry being marked as applied.
+        store.commit().unwrap();
+        assert_eq!(search_ids(&store, "first"), vec![first]);
+
+        let second = store.create(&Flaky { title: "flaky second".into() }).unwrap();
+
+        FAIL.store(true, Ordering::SeqCst);
+        assert!(store.repair().is_err());
+        FAIL.store(false, Ordering::SeqCst);
+
+        // Not committed when dropped, with `CommitPolicy::Manual`.
+        drop(store);
+
+        second
+    };
+
+    // The logged entry is still pending, so is replayed when the `Store` is opened again.
+    let store = builder::<Flaky>(dir.path()).finish().unwrap();
+    assert_eq!(search_ids(&store, "second"), vec![id]);
+    assert!(store.verify().unwrap().is_ok());
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use common::{open, search_ids, temp_dir, Note};
+use pallet::{Document, Store};
+use std::sync::Arc;
+
+#[test]
+fn concurrent_updates_index_latest_value() {
+    let dir = temp_dir();
+    let store: Arc<Store<Note>> = Arc::new(open(dir.path()));
+
+    let id = store.create(&Note::new("start", 0)).unwrap();
+
+    let threads = (1..=8)
+        .map(|thread| {
+            let store = store.clone();
+            std::thread::spawn(move || {
+                for i in 0..10 {
+                    let n = thread * 100 + i;
+                    store.upsert(&Document::new(id, Note::new("update", n))).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let stored = store.find(id).unwrap().unwrap();
+
+    assert_eq!(search_ids(&store, &format!("n:{}", stored.n)), vec![id]);
+    assert_eq!(search_ids(&store, "update"), vec![id]);
+    assert!(store.verify().unwrap().is_ok());
+}
+
+#[test]
+fn concurrent_update_with_indexes_latest_value() {
+    let dir = temp_dir();
+    let store: Arc<Store<Note>> = Arc::new(open(dir.path()));
+
+    let id = store.create(&Note::new("counter", 0)).unwrap();
+
+    let threads = (0..8)
+        .map(|_| {
+            let store = store.clone();
+            std::thread::spawn(move || {
+                for _ in 0..10 {
+                    store.update_with(id, |note| note.n += 1).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    assert_eq!(store.find(id).unwrap().unwrap().n, 80);
+    assert_eq!(search_ids(&store, "n:80"), vec![id]);
+}
+
+#[test]
+fn delete_all_keeps_later_creates() {
+    let dir = temp_dir();
+    let store = open::<Note>(dir.path());
+
+    store.create_multi(&[Note::new("one", 1), Note::new("two", 2)]).unwrap();
+    store.delete_all().unwrap();
+
+    let id = store.create(&Note::new("three", 3)).unwrap();
+
+    assert!(search_ids(&store, "one two").is_empty());
+    assert_eq!(search_ids(&store, "three"), vec![id]);
+    assert!(store.verify().unwrap().is_ok());
+}
+
+#[test]
+fn recover_removes_orphans_from_restored_index() {
+    let dir = temp_dir();
+    let index_backup = temp_dir();
+
+    let (kept, deleted) = {
+        let store = open::<Note>(dir.path());
+        let kept = store.create(&Note::new("kept", 1)).unwrap();
+        let deleted = store.create(&Note::new("deleted", 2)).unwrap();
+        (kept, deleted)
+    };
+
+    copy_index(dir.path(), index_backup.path());
+
+    {
+        let store = open::<Note>(dir.path());
+        store.delete(deleted).unwrap();
+        store.create(&Note::new("other", 3)).unwrap();
+        // Two more commits, so the restored index is older than the recorded opstamp.
+        store.update_with(kept, |note| note.n = 10).unwrap();
+    }
+
+    // Restore the index from before the delete.
+    copy_index(index_backup.path(), dir.path());
+
+    let store = open::<Note>(dir.path());
+
+    assert!(search_ids(&store, "deleted").is_empty());
+    assert_eq!(search_ids(&store, "n:10"), vec![kept]);
+    assert!(store.verify().unwrap().is_ok());
+}
+
+// Copy the index files (everything but the `sled` db) from `from` to `to`.
+fn copy_index(from: &std::path::Path, to: &std::path::Path) {
+    for entry in std::fs::read_dir(to).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_file() {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    for entry in std::fs::read_dir(from).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_file() {
+            std::fs::copy(&path, to.join(path.file_name().unwrap())).unwrap();
+        }
+    }
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::{open, temp_dir, Note};
+use pallet::{err, Document};
+use std::ops::Bound;
+
+fn summaries<I: Iterator<Item = err::Result<Document<Note>>>>(docs: I) -> Vec<(u64, u64)> {
+    docs.map(|doc| doc.unwrap()).map(|doc| (doc.id, doc.inner.n)).collect()
+}
+
+#[test]
+fn iterates_lazily_in_id_order() {
+    let dir = temp_dir();
+    let store = open::<Note>(dir.path());
+
+    for id in &[3, 1, 300, 2, 256] {
+        store.insert_with_id(*id, &Note::new("note", *id)).unwrap();
+    }
+    store.delete(2).unwrap();
+
+    let all = vec![(1, 1), (3, 3), (256, 256), (300, 300)];
+    assert_eq!(summaries(store.iter()), all);
+    assert_eq!(summaries(store.iter_rev()), all.iter().rev().copied().collect::<Vec<_>>());
+    assert_eq!(store.all().unwrap().len(), 4);
+
+    assert_eq!(summaries(store.range(2..300)), vec![(3, 3), (256, 256)]);
+    assert_eq!(summaries(store.range(..=3).rev()), vec![(3, 3), (1, 1)]);
+    assert_eq!(
+        summaries(store.range((Bound::Excluded(3), Bound::Unbounded))),
+        vec![(256, 256), (300, 300)]
+    );
+    assert!(summaries(store.range(4..256)).is_empty());
+
+    // Both ends can be consumed from the same iterator.
+    let mut iter = store.iter();
+    assert_eq!(iter.next().unwrap().unwrap().id, 1);
+    assert_eq!(iter.next_back().unwrap().unwrap().id, 300);
+    assert_eq!(summaries(iter), vec![(3, 3), (256, 256)]);
+}
+
+#[test]
+fn iterated_documents_include_their_versions() {
+    let dir = temp_dir();
+    let store = open::<Note>(dir.path());
+
+    let ids =
+        store.create_multi(&(0..6).map(|n| Note::new("note", n)).collect::<Vec<_>>()).unwrap();
+
+    for (times, id) in ids.iter().enumerate() {
+        for _ in 0..times {
+            let doc = store.find(*id).unwrap().unwrap();
+            store.update(&doc).unwrap();
+        }
+    }
+    store.delete(ids[1]).unwrap();
+    store.delete(ids[4]).unwrap();
+
+    let expected = |docs: Vec<u64>| {
+        docs.into_iter()
+            .map(|id| (id, store.find(id).unwrap().unwrap().version()))
+            .collect::<Vec<_>>()
+    };
+    let versions = |docs: Vec<Document<Note>>| {
+        docs.into_iter().map(|doc| (doc.id, doc.version())).collect::<Vec<_>>()
+    };
+
+    let forward = store.iter().collect::<err::Result<Vec<_>>>().unwrap();
+    assert_eq!(versions(forward), expected(vec![ids[0], ids[2], ids[3], ids[5]]));
+
+    let backward = store.iter_rev().collect::<err::Result<Vec<_>>>().unwrap();
+    assert_eq!(versions(backward), expected(vec![ids[5], ids[3], ids[2], ids[0]]));
+
+    // Versions found by iterating can be used for conditional updates.
+    for doc in store.range(ids[2]..).collect::<err::Result<Vec<_>>>().unwrap() {
+        assert!(store.update_if(doc.id, doc.version().unwrap(), &doc.inner).unwrap().is_some());
+    }
+}
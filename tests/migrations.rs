@@ -0,0 +1,137 @@
+mod common;
+
+use common::{builder, open, search_ids, temp_dir, Note};
+use pallet::Store;
+use std::convert::TryInto;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[test]
+fn little_endian_keys_are_converted_when_first_opened() {
+    let dir = temp_dir();
+
+    {
+        let store = open::<Note>(dir.path());
+        for id in &[1, 2, 256] {
+            store.insert_with_id(*id, &Note::new("legacy", *id)).unwrap();
+        }
+    }
+
+    // Rewrite the tree as an earlier version would have written it.
+    {
+        let db = common::open_db(dir.path());
+        let tree = db.open_tree("notes").unwrap();
+        for res in tree.iter().collect::<Vec<_>>() {
+            let (key, value) = res.unwrap();
+            let id = u64::from_be_bytes(key.as_ref().try_into().unwrap());
+            tree.remove(key).unwrap();
+            tree.insert(id.to_le_bytes(), value).unwrap();
+        }
+        db.open_tree("notes/__meta__").unwrap().remove("key_format").unwrap();
+    }
+
+    {
+        let store = open::<Note>(dir.path());
+
+        let ids = store.iter().map(|doc| doc.unwrap().id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 2, 256]);
+        assert_eq!(store.find(256).unwrap().unwrap().n, 256);
+    }
+
+    // The recorded key format version means keys are only converted once.
+    let store = open::<Note>(dir.path());
+    assert_eq!(store.iter().map(|doc| doc.unwrap().id).collect::<Vec<_>>(), vec![1, 2, 256]);
+    drop(store);
+
+    // Trees written by later key formats are not opened.
+    {
+        let db = common::open_db(dir.path());
+        db.open_tree("notes/__meta__").unwrap().insert("key_format", &2u64.to_be_bytes()).unwrap();
+    }
+    assert!(builder::<Note>(dir.path()).finish().is_err());
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, pallet::DocumentLike)]
+#[pallet(tree_name = "notes")]
+pub struct TaggedNote {
+    #[pallet(default_search_field)]
+    title: String,
+    #[pallet(index_field_type = "u64")]
+    n: u64,
+    tag: String,
+}
+
+fn tag_note(old: Note) -> TaggedNote {
+    TaggedNote { tag: format!("tag{}", old.n), title: old.title, n: old.n }
+}
+
+#[test]
+fn documents_are_migrated_and_reindexed_once() {
+    let dir = temp_dir();
+
+    let ids = {
+        let store = open::<Note>(dir.path());
+        store.create_multi(&[Note::new("alpha", 1), Note::new("beta", 2)]).unwrap()
+    };
+
+    let runs = Arc::new(AtomicUsize::new(0));
+    let migrate = |runs: Arc<AtomicUsize>| {
+        move |old: Note| {
+            runs.fetch_add(1, Ordering::SeqCst);
+            tag_note(old)
+        }
+    };
+
+    {
+        let store: Store<TaggedNote> =
+            builder(dir.path()).with_migration(0, migrate(runs.clone())).finish().unwrap();
+
+        assert_eq!(store.find(ids[1]).unwrap().unwrap().tag, "tag2");
+        assert_eq!(search_ids(&store, "tag:tag1"), vec![ids[0]]);
+        assert_eq!(search_ids(&store, "alpha OR beta"), ids);
+    }
+
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+    // Already at the latest version.
+    let store: Store<TaggedNote> =
+        builder(dir.path()).with_migration(0, migrate(runs.clone())).finish().unwrap();
+    assert_eq!(store.all().unwrap().len(), 2);
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn new_trees_start_at_the_latest_version() {
+    let dir = temp_dir();
+
+    {
+        let store: Store<TaggedNote> = builder(dir.path())
+            .with_migration(0, |_: Note| -> TaggedNote { panic!("migrated a new tree") })
+            .finish()
+            .unwrap();
+        store.create(&tag_note(Note::new("alpha", 1))).unwrap();
+    }
+
+    let store: Store<TaggedNote> = builder(dir.path())
+        .with_migration(0, |_: Note| -> TaggedNote { panic!("migrated twice") })
+        .finish()
+        .unwrap();
+    assert_eq!(store.all().unwrap()[0].tag, "tag1");
+}
+
+#[test]
+fn missing_migrations_fail_to_open() {
+    let dir = temp_dir();
+
+    {
+        let store = open::<Note>(dir.path());
+        store.create(&Note::new("alpha", 1)).unwrap();
+    }
+
+    // No migration from version `0`.
+    let res = builder::<TaggedNote>(dir.path()).with_migration(1, tag_note).finish();
+    assert!(res.is_err());
+
+    // The tree is left unchanged.
+    assert_eq!(open::<Note>(dir.path()).all().unwrap()[0].inner, Note::new("alpha", 1));
+}
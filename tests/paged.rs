@@ -0,0 +1,28 @@
+mod common;
+
+use common::{open, temp_dir, Note};
+use pallet::search::{Page, Paged};
+
+#[test]
+fn pages_through_equally_scored_results_in_id_order() {
+    let dir = temp_dir();
+    let store = open::<Note>(dir.path());
+
+    let notes = (0..15).map(|n| Note::new("note", n)).collect::<Vec<_>>();
+    let ids = store.create_multi(&notes).unwrap();
+    store.create(&Note::new("other", 15)).unwrap();
+
+    let page_ids = |paged: Paged<&str>| {
+        let results = store.search(paged).unwrap();
+        assert_eq!(results.count, 15);
+        results.hits.into_iter().map(|hit| hit.doc.id).collect::<Vec<_>>()
+    };
+
+    // Defaults to the first 10 results.
+    assert_eq!(page_ids(Paged::new("note")), ids[..10]);
+    assert_eq!(page_ids(Paged::new("note").with_offset(10)), ids[10..]);
+    assert_eq!(page_ids(Paged::new("note").with_offset(4).with_limit(3)), ids[4..7]);
+    assert_eq!(page_ids(Paged::new("note").with_page(Page::new(12, 10))), ids[12..]);
+    assert!(page_ids(Paged::new("note").with_offset(20)).is_empty());
+    assert!(page_ids(Paged::new("note").with_limit(0)).is_empty());
+}
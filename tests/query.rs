@@ -0,0 +1,110 @@
+mod common;
+
+use common::{open, temp_dir, Note};
+use pallet::search::Query;
+use pallet::Store;
+
+fn query_ids(store: &Store<Note>, query: &Query) -> Vec<u64> {
+    let mut ids = store
+        .search(query)
+        .unwrap()
+        .hits
+        .into_iter()
+        .map(|hit| hit.doc.inner.n)
+        .collect::<Vec<_>>();
+    ids.sort_unstable();
+    ids
+}
+
+fn create_notes(store: &Store<Note>) {
+    store
+        .create_multi(&[
+            Note::new("the old man and the sea", 1),
+            Note::new("the man who sold the world", 2),
+            Note::new("old world wine", 3),
+            Note::new("sea shanties", 4),
+        ])
+        .unwrap();
+}
+
+#[test]
+fn leaf_queries_match_by_field() {
+    let dir = temp_dir();
+    let store = open::<Note>(dir.path());
+    create_notes(&store);
+
+    assert_eq!(query_ids(&store, &Query::all()), vec![1, 2, 3, 4]);
+
+    // Text terms are tokenized and all must be present.
+    assert_eq!(query_ids(&store, &Query::term("title", "Sea")), vec![1, 4]);
+    assert_eq!(query_ids(&store, &Query::term("title", "world old")), vec![3]);
+    assert_eq!(query_ids(&store, &Query::term("n", 2u64)), vec![2]);
+
+    assert_eq!(query_ids(&store, &Query::phrase("title", "old man")), vec![1]);
+    assert_eq!(query_ids(&store, &Query::phrase("title", "man old")), Vec::<u64>::new());
+
+    assert_eq!(query_ids(&store, &Query::range("n", 2u64..4)), vec![2, 3]);
+    assert_eq!(query_ids(&store, &Query::range("n", 3u64..)), vec![3, 4]);
+
+    assert_eq!(query_ids(&store, &Query::fuzzy("title", "wrld", 1)), vec![2, 3]);
+    assert_eq!(query_ids(&store, &Query::fuzzy("title", "wrld", 0)), Vec::<u64>::new());
+
+    assert_eq!(query_ids(&store, &Query::prefix("title", "sea sha")), vec![4]);
+    assert_eq!(query_ids(&store, &Query::prefix("title", "wi")), vec![3]);
+}
+
+#[test]
+fn boolean_queries_combine_clauses() {
+    let dir = temp_dir();
+    let store = open::<Note>(dir.path());
+    create_notes(&store);
+
+    assert_eq!(query_ids(&store, &Query::boolean()), Vec::<u64>::new());
+
+    let either =
+        Query::boolean().should(Query::term("title", "sea")).should(Query::term("title", "wine"));
+    assert_eq!(query_ids(&store, &either), vec![1, 3, 4]);
+
+    let both = Query::term("title", "old").must(Query::term("title", "sea"));
+    assert_eq!(query_ids(&store, &both), vec![1]);
+
+    let excluded = Query::all().must_not(Query::range("n", ..3u64));
+    assert_eq!(query_ids(&store, &excluded), vec![3, 4]);
+}
+
+#[test]
+fn boost_changes_the_order_of_results() {
+    let dir = temp_dir();
+    let store = open::<Note>(dir.path());
+    create_notes(&store);
+
+    let order = |query: Query| {
+        store
+            .search(&query)
+            .unwrap()
+            .hits
+            .into_iter()
+            .map(|hit| hit.doc.inner.n)
+            .collect::<Vec<_>>()
+    };
+
+    let wine = Query::term("title", "wine");
+    let shanties = Query::term("title", "shanties");
+
+    let ns = order(Query::boolean().should(wine.clone().boost(10.0)).should(shanties.clone()));
+    assert_eq!(ns, vec![3, 4]);
+
+    let ns = order(Query::boolean().should(wine).should(shanties.boost(10.0)));
+    assert_eq!(ns, vec![4, 3]);
+}
+
+#[test]
+fn unknown_fields_and_mismatched_values_are_errors() {
+    let dir = temp_dir();
+    let store = open::<Note>(dir.path());
+    create_notes(&store);
+
+    assert!(store.search(&Query::term("missing", "sea")).is_err());
+    assert!(store.search(&Query::range("n", "a".."b")).is_err());
+    assert!(store.search(&Query::term("n", -1i64)).is_err());
+}
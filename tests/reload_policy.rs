@@ -0,0 +1,61 @@
+mod common;
+
+use common::{open_db, temp_dir, Note};
+use pallet::{DocumentLike, Store};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+fn open_with(dir: &Path, reload_policy: Option<tantivy::ReloadPolicy>) -> Store<Note> {
+    let mut index_builder = Note::index_builder();
+    if let Some(reload_policy) = reload_policy {
+        index_builder = index_builder.with_reload_policy(reload_policy);
+    }
+
+    Store::builder()
+        .with_index_builder(index_builder)
+        .with_db(open_db(dir))
+        .with_index_dir(dir)
+        .finish()
+        .unwrap()
+}
+
+// Adds a document to the index in `dir` with a separate `tantivy::IndexWriter`.
+fn write_elsewhere(dir: &Path) {
+    let index = tantivy::Index::open_in_dir(dir).unwrap();
+    let title = index.schema().get_field("title").unwrap();
+    let mut writer = index.writer(50_000_000).unwrap();
+    writer.add_document(tantivy::doc!(title => "alpha"));
+    writer.commit().unwrap();
+}
+
+fn num_docs(store: &Store<Note>) -> u64 {
+    store.index.reader().searcher().num_docs()
+}
+
+#[test]
+fn manual_reload_policy_ignores_commits_made_elsewhere() {
+    let dir = temp_dir();
+    let store = open_with(dir.path(), None);
+
+    write_elsewhere(dir.path());
+    std::thread::sleep(Duration::from_millis(500));
+    assert_eq!(num_docs(&store), 0);
+
+    store.index.reader().reload().unwrap();
+    assert_eq!(num_docs(&store), 1);
+}
+
+#[test]
+fn on_commit_reload_policy_picks_up_commits_made_elsewhere() {
+    let dir = temp_dir();
+    let store = open_with(dir.path(), Some(tantivy::ReloadPolicy::OnCommit));
+
+    write_elsewhere(dir.path());
+
+    // The reader is reloaded in the background once the change to `meta.json` is noticed.
+    let start = Instant::now();
+    while num_docs(&store) == 0 && start.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(num_docs(&store), 1);
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use common::{builder, open, search_ids, temp_dir, Note};
+use pallet::search::{Order, SchemaDifference, Sorted};
+use pallet::{err, SchemaMismatchPolicy, Store};
+
+// `Note` with a fast `n` field, stored the same way.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, pallet::DocumentLike)]
+#[pallet(tree_name = "notes")]
+pub struct FastNote {
+    #[pallet(default_search_field)]
+    title: String,
+    #[pallet(
+        index_field_type = "u64",
+        index_field_options = "tantivy::schema::INDEXED | tantivy::schema::FAST"
+    )]
+    n: u64,
+}
+
+fn create_notes(dir: &std::path::Path) -> Vec<u64> {
+    let store = open::<Note>(dir);
+    store.create_multi(&[Note::new("alpha", 2), Note::new("beta", 1)]).unwrap()
+}
+
+#[test]
+fn schema_mismatches_list_differences() {
+    let dir = temp_dir();
+    create_notes(dir.path());
+
+    match builder::<FastNote>(dir.path()).finish() {
+        Err(err::Error::SchemaMismatch { differences }) => {
+            assert_eq!(differences.len(), 1);
+            assert!(matches!(differences[0], SchemaDifference::OptionsChanged { .. }));
+            assert_eq!(differences[0].field_name(), "n");
+        }
+        other => panic!("unexpected result {:?}", other.map(|_| ())),
+    }
+
+    // The existing index is left as it was.
+    let store = open::<Note>(dir.path());
+    assert_eq!(search_ids(&store, "alpha OR beta").len(), 2);
+}
+
+#[test]
+fn recreate_policy_reindexes_with_the_new_schema() {
+    let dir = temp_dir();
+    let ids = create_notes(dir.path());
+
+    let store: Store<FastNote> = builder(dir.path())
+        .with_schema_mismatch_policy(SchemaMismatchPolicy::Recreate)
+        .finish()
+        .unwrap();
+
+    assert_eq!(search_ids(&store, "alpha OR beta"), ids);
+
+    let sorted =
+        store.search(Sorted::new("alpha OR beta").with_sort_field("n", Order::Asc)).unwrap();
+    let ns = sorted.hits.iter().map(|hit| hit.doc.n).collect::<Vec<_>>();
+    assert_eq!(ns, vec![1, 2]);
+}
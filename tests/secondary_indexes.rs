@@ -0,0 +1,126 @@
+mod common;
+
+use common::{open, temp_dir};
+use pallet::{err, Document, Store};
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, pallet::DocumentLike)]
+#[pallet(tree_name = "users")]
+pub struct User {
+    #[pallet(default_search_field, unique)]
+    email: String,
+    #[pallet(index_field_type = "u64", sled_index)]
+    age: u64,
+}
+
+// The same tree as `User`, before its fields were indexed in `sled`.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, pallet::DocumentLike)]
+#[pallet(tree_name = "users")]
+pub struct UnindexedUser {
+    #[pallet(default_search_field)]
+    email: String,
+    #[pallet(index_field_type = "u64")]
+    age: u64,
+}
+
+fn user(email: &str, age: u64) -> User {
+    User { email: email.into(), age }
+}
+
+fn inners<I: Iterator<Item = err::Result<Document<User>>>>(docs: I) -> Vec<User> {
+    docs.map(|doc| doc.unwrap().inner).collect()
+}
+
+fn is_conflict(res: err::Result<impl std::fmt::Debug>, expected_id: u64) -> bool {
+    matches!(res, Err(err::Error::UniqueConflict { ref field, id }) if field == "email" && id == expected_id)
+}
+
+#[test]
+fn unique_fields_are_kept_in_sync() {
+    let dir = temp_dir();
+    let store = open::<User>(dir.path());
+
+    let alice = store.create(&user("alice@example.com", 30)).unwrap();
+    let bob = store.create(&user("bob@example.com", 40)).unwrap();
+
+    assert_eq!(store.find_by_unique("email", "alice@example.com").unwrap().unwrap().id, alice);
+    assert!(store.find_by_unique("email", "carol@example.com").unwrap().is_none());
+    assert!(store.find_by_unique("age", "alice@example.com").is_err());
+
+    assert!(is_conflict(store.create(&user("alice@example.com", 50)), alice));
+    assert!(is_conflict(store.update(&Document::new(bob, user("alice@example.com", 40))), alice));
+
+    // A conflict within a batch writes nothing.
+    let batch = [user("carol@example.com", 20), user("carol@example.com", 21)];
+    assert!(store.create_multi(&batch).is_err());
+    assert!(store.find_by_unique("email", "carol@example.com").unwrap().is_none());
+    assert_eq!(store.all().unwrap().len(), 2);
+
+    // Updates and deletes free the old value.
+    store.update(&Document::new(alice, user("alice@example.org", 30))).unwrap();
+    assert!(store.find_by_unique("email", "alice@example.com").unwrap().is_none());
+    store.delete(bob).unwrap();
+    assert!(store.find_by_unique("email", "bob@example.com").unwrap().is_none());
+
+    let carol = store.create(&user("alice@example.com", 25)).unwrap();
+    let bob = store.create(&user("bob@example.com", 45)).unwrap();
+    assert_eq!(store.find_by_unique("email", "alice@example.com").unwrap().unwrap().id, carol);
+    assert_eq!(store.find_by_unique("email", "bob@example.com").unwrap().unwrap().id, bob);
+}
+
+#[test]
+fn sled_indexes_find_and_range_by_value() {
+    let dir = temp_dir();
+    let store = open::<User>(dir.path());
+
+    let ids = store
+        .create_multi(&[
+            user("a@example.com", 30),
+            user("b@example.com", 20),
+            user("c@example.com", 30),
+            user("d@example.com", 40),
+        ])
+        .unwrap();
+
+    assert_eq!(
+        inners(store.find_by("age", &30u64).unwrap()),
+        vec![user("a@example.com", 30), user("c@example.com", 30)]
+    );
+    assert_eq!(
+        inners(store.range_by("age", 20u64..40).unwrap()),
+        vec![user("b@example.com", 20), user("a@example.com", 30), user("c@example.com", 30)]
+    );
+    assert_eq!(
+        inners(
+            store
+                .range_by("age", (std::ops::Bound::Excluded(30u64), std::ops::Bound::Unbounded))
+                .unwrap()
+                .rev()
+        ),
+        vec![user("d@example.com", 40)]
+    );
+    assert!(store.find_by("email", "a@example.com").is_err());
+
+    store.update(&Document::new(ids[0], user("a@example.com", 41))).unwrap();
+    store.delete(ids[2]).unwrap();
+
+    assert!(inners(store.find_by("age", &30u64).unwrap()).is_empty());
+    assert_eq!(
+        inners(store.range_by("age", 40u64..).unwrap()),
+        vec![user("d@example.com", 40), user("a@example.com", 41)]
+    );
+}
+
+#[test]
+fn indexes_are_built_from_existing_documents() {
+    let dir = temp_dir();
+
+    let id = {
+        let store = open::<UnindexedUser>(dir.path());
+        store.create(&UnindexedUser { email: "a@example.com".into(), age: 30 }).unwrap()
+    };
+
+    let store: Store<User> = open(dir.path());
+
+    assert_eq!(store.find_by_unique("email", "a@example.com").unwrap().unwrap().id, id);
+    assert_eq!(inners(store.find_by("age", &30u64).unwrap()), vec![user("a@example.com", 30)]);
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::{open, temp_dir};
+use pallet::search::Suggestion;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, pallet::DocumentLike)]
+#[pallet(tree_name = "titles")]
+pub struct Title {
+    #[pallet(default_search_field, suggest)]
+    title: String,
+}
+
+fn suggestion(text: &str, count: u64) -> Suggestion {
+    Suggestion { text: text.into(), count }
+}
+
+#[test]
+fn suggests_most_common_terms_across_segments() {
+    let dir = temp_dir();
+    let store = open::<Title>(dir.path());
+
+    // Each write is committed separately, so terms are spread over several segments.
+    for title in &["old man", "old map", "the sea", "old mast", "old man", "old mast", "old man"] {
+        store.create(&Title { title: title.to_string() }).unwrap();
+    }
+
+    assert_eq!(
+        store.suggest("old ma", "title", 10).unwrap(),
+        vec![suggestion("old man", 3), suggestion("old mast", 2), suggestion("old map", 1)]
+    );
+
+    // Ties are broken alphabetically, and only the top `limit` are returned.
+    store.create(&Title { title: "map".into() }).unwrap();
+    assert_eq!(
+        store.suggest("MA", "title", 2).unwrap(),
+        vec![suggestion("man", 3), suggestion("map", 2)]
+    );
+
+    assert!(store.suggest("old ", "title", 10).unwrap().is_empty());
+    assert!(store.suggest("old ma", "title", 0).unwrap().is_empty());
+    assert!(store.suggest("old ma", "missing", 10).is_err());
+}
+
+#[test]
+fn deleted_and_updated_documents_are_not_counted() {
+    let dir = temp_dir();
+    let store = open::<Title>(dir.path());
+
+    let ids = store
+        .create_multi(&[
+            Title { title: "old man".into() },
+            Title { title: "old man".into() },
+            Title { title: "old map".into() },
+        ])
+        .unwrap();
+
+    store.delete(ids[0]).unwrap();
+    assert_eq!(
+        store.suggest("old ma", "title", 10).unwrap(),
+        vec![suggestion("old man", 1), suggestion("old map", 1)]
+    );
+
+    // The replaced document is deleted, so its old terms are no longer suggested.
+    let mut doc = store.find(ids[2]).unwrap().unwrap();
+    doc.inner.title = "old man".into();
+    store.update(&doc).unwrap();
+    assert_eq!(store.suggest("old ma", "title", 10).unwrap(), vec![suggestion("old man", 2)]);
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use common::{open_db, search_ids, temp_dir, Flaky, Note, FAIL};
+use pallet::{err, CommitPolicy, Store, Transaction};
+use std::path::Path;
+use std::sync::atomic::Ordering;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, pallet::DocumentLike)]
+#[pallet(tree_name = "archive")]
+pub struct Archived {
+    #[pallet(default_search_field)]
+    title: String,
+}
+
+// Opens a `Store` with its own index directory in `dir`.
+fn open_in<T: pallet::DocumentLike>(db: &sled::Db, dir: &Path, name: &str) -> Store<T> {
+    let index_dir = dir.join(name);
+    std::fs::create_dir_all(&index_dir).unwrap();
+    Store::builder().with_db(db.clone()).with_index_dir(index_dir).finish().unwrap()
+}
+
+#[test]
+fn changes_to_several_stores_are_committed_together() {
+    let dir = temp_dir();
+    let db = open_db(dir.path());
+    let notes = open_in::<Note>(&db, dir.path(), "notes");
+    let archive = open_in::<Archived>(&db, dir.path(), "archive");
+
+    let id = notes.create(&Note::new("alpha", 1)).unwrap();
+
+    let archived_id = Transaction::new()
+        .with_store(&notes)
+        .with_store(&archive)
+        .run(|txn| {
+            let notes = txn.store(&notes)?;
+            let note = notes.find(id)?.unwrap();
+            notes.delete(id)?;
+            assert!(notes.find(id)?.is_none());
+
+            let archive = txn.store(&archive)?;
+            let archived_id = archive.create(&Archived { title: note.inner.title })?;
+            assert_eq!(archive.find(archived_id)?.unwrap().title, "alpha");
+            Ok(archived_id)
+        })
+        .unwrap();
+
+    assert!(notes.find(id).unwrap().is_none());
+    assert_eq!(archive.find(archived_id).unwrap().unwrap().title, "alpha");
+
+    assert!(search_ids(&notes, "alpha").is_empty());
+    assert_eq!(search_ids(&archive, "alpha"), vec![archived_id]);
+}
+
+#[test]
+fn errors_abort_every_change() {
+    let dir = temp_dir();
+    let db = open_db(dir.path());
+    let notes = open_in::<Note>(&db, dir.path(), "notes");
+    let archive = open_in::<Archived>(&db, dir.path(), "archive");
+
+    let id = notes.create(&Note::new("alpha", 1)).unwrap();
+
+    let res = Transaction::new().with_store(&notes).with_store(&archive).run(|txn| {
+        txn.store(&notes)?.delete(id)?;
+        txn.store(&archive)?.create(&Archived { title: "alpha".into() })?;
+        Err::<(), _>(err::custom("abort").into())
+    });
+    assert!(res.is_err());
+
+    assert_eq!(notes.find(id).unwrap().unwrap().inner, Note::new("alpha", 1));
+    assert!(archive.all().unwrap().is_empty());
+
+    assert_eq!(search_ids(&notes, "alpha"), vec![id]);
+    assert!(search_ids(&archive, "alpha").is_empty());
+}
+
+#[test]
+fn stores_must_be_added_and_share_a_db() {
+    let dir = temp_dir();
+    let db = open_db(dir.path());
+    let notes = open_in::<Note>(&db, dir.path(), "notes");
+    let archive = open_in::<Archived>(&db, dir.path(), "archive");
+
+    let res = Transaction::new().with_store(&notes).run(|txn| {
+        txn.store(&archive)?.create(&Archived { title: "alpha".into() })?;
+        Ok(())
+    });
+    assert!(res.is_err());
+    assert!(archive.all().unwrap().is_empty());
+
+    let other_dir = temp_dir();
+    let other = open_in::<Archived>(&open_db(other_dir.path()), other_dir.path(), "archive");
+
+    let res = Transaction::new().with_store(&notes).with_store(&other).run(|txn| {
+        txn.store(&other)?.create(&Archived { title: "alpha".into() })?;
+        Ok(())
+    });
+    assert!(res.is_err());
+    assert!(other.all().unwrap().is_empty());
+}
+
+#[test]
+fn index_errors_in_one_store_do_not_stop_the_others() {
+    let dir = temp_dir();
+    let db = open_db(dir.path());
+    let notes = open_in::<Note>(&db, dir.path(), "notes");
+    let archive = open_in::<Archived>(&db, dir.path(), "archive");
+
+    std::fs::create_dir_all(dir.path().join("flaky")).unwrap();
+    let flaky: Store<Flaky> = Store::builder()
+        .with_db(db.clone())
+        .with_index_dir(dir.path().join("flaky"))
+        .with_commit_policy(CommitPolicy::Manual)
+        .finish()
+        .unwrap();
+
+    let first = flaky.create(&Flaky { title: "flaky first".into() }).unwrap();
+
+    // Leaves the rolled back change to be replayed by the next index change, which then fails.
+    FAIL.store(true, Ordering::SeqCst);
+    assert!(flaky.repair().is_err());
+
+    let res =
+        Transaction::new().with_store(&notes).with_store(&flaky).with_store(&archive).run(|txn| {
+            let note_id = txn.store(&notes)?.create(&Note::new("alpha", 1))?;
+            let second = txn.store(&flaky)?.create(&Flaky { title: "second".into() })?;
+            let archived_id = txn.store(&archive)?.create(&Archived { title: "alpha".into() })?;
+            Ok((note_id, second, archived_id))
+        });
+    FAIL.store(false, Ordering::SeqCst);
+    assert!(res.is_err());
+
+    // The transaction committed, and the stores after the failed one were still indexed.
+    let (note_id, archived_id) = match (notes.iter().next(), archive.iter().next()) {
+        (Some(note), Some(archived)) => (note.unwrap().id, archived.unwrap().id),
+        other => panic!("expected committed documents, got {:?}", other),
+    };
+    assert_eq!(search_ids(&notes, "alpha"), vec![note_id]);
+    assert_eq!(search_ids(&archive, "alpha"), vec![archived_id]);
+
+    // The failed store replays its logged changes.
+    flaky.commit().unwrap();
+    assert_eq!(search_ids(&flaky, "first"), vec![first]);
+    assert_eq!(search_ids(&flaky, "second").len(), 1);
+    assert!(flaky.verify().unwrap().is_ok());
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::{open, temp_dir, Note};
+use pallet::{err, Document};
+
+#[test]
+fn updates_increment_versions() {
+    let dir = temp_dir();
+    let store = open::<Note>(dir.path());
+
+    let id = store.create(&Note::new("alpha", 1)).unwrap();
+
+    let mut doc = store.find(id).unwrap().unwrap();
+    assert_eq!(doc.version(), Some(0));
+
+    doc.n = 2;
+    store.update(&doc).unwrap();
+
+    // Updating the same loaded `Document` again conflicts, as it has since been updated.
+    doc.n = 3;
+    match store.update(&doc) {
+        Err(err::Error::VersionConflict { expected: 0, actual: Some(1), .. }) => {}
+        other => panic!("expected a version conflict, got {:?}", other),
+    }
+    assert!(store.update_multi(&[doc.clone()]).is_err());
+    assert_eq!(store.find(id).unwrap().unwrap().n, 2);
+
+    // Unversioned `Document`s overwrite regardless.
+    store.update(&doc.with_version(None)).unwrap();
+
+    let doc = store.find(id).unwrap().unwrap();
+    assert_eq!((doc.n, doc.version()), (3, Some(2)));
+
+    let updated = store.update_with(id, |note| note.n = 4).unwrap().unwrap();
+    assert_eq!(updated.version(), Some(3));
+}
+
+#[test]
+fn update_if_checks_version() {
+    let dir = temp_dir();
+    let store = open::<Note>(dir.path());
+
+    let id = store.create(&Note::new("alpha", 1)).unwrap();
+
+    assert_eq!(store.update_if(id, 0, &Note::new("alpha", 2)).unwrap(), Some(1));
+    assert_eq!(store.update_if(id, 0, &Note::new("alpha", 3)).unwrap(), None);
+    assert_eq!(store.update_if(id + 1, 0, &Note::new("alpha", 3)).unwrap(), None);
+
+    assert_eq!(store.find(id).unwrap().unwrap().n, 2);
+}
+
+#[test]
+fn upsert_checks_version_if_set() {
+    let dir = temp_dir();
+    let store = open::<Note>(dir.path());
+
+    assert_eq!(store.upsert(&Document::new(7, Note::new("alpha", 1))).unwrap(), 0);
+    assert_eq!(store.upsert(&Document::new(7, Note::new("alpha", 2))).unwrap(), 1);
+
+    let stale = store.find(7).unwrap().unwrap().with_version(Some(0));
+
+    match store.upsert(&stale) {
+        Err(err::Error::VersionConflict { id: 7, expected: 0, actual: Some(1) }) => {}
+        other => panic!("expected a version conflict, got {:?}", other),
+    }
+
+    let mut doc = store.find(7).unwrap().unwrap();
+    doc.n = 3;
+    assert_eq!(store.upsert(&doc).unwrap(), 2);
+
+    // Versions keep increasing after a delete, so versions loaded before it never match again.
+    store.delete(7).unwrap();
+    assert!(store.upsert(&doc.clone().with_version(Some(2))).is_err());
+    assert_eq!(store.upsert(&Document::new(7, Note::new("alpha", 4))).unwrap(), 3);
+    assert!(store.update(&doc.with_version(Some(2))).is_err());
+    assert_eq!(store.find(7).unwrap().unwrap().version(), Some(3));
+}
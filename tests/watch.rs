@@ -0,0 +1,90 @@
+mod common;
+
+use common::{temp_dir, Note};
+use pallet::{db, Document, Store};
+use std::time::Duration;
+
+fn open_with_db(dir: &std::path::Path) -> (Store<Note>, sled::Db) {
+    let db = common::open_db(dir);
+    let store = Store::builder().with_db(db.clone()).with_index_dir(dir).finish().unwrap();
+    (store, db)
+}
+
+#[test]
+fn watch_sees_creates_updates_and_deletes() {
+    let dir = temp_dir();
+    let (store, _) = open_with_db(dir.path());
+
+    let events = store.watch().into_channel();
+
+    let id = store.create(&Note::new("alpha", 1)).unwrap();
+    store.update(&Document::new(id, Note::new("alpha", 2))).unwrap();
+    store.delete(id).unwrap();
+
+    let timeout = Duration::from_secs(5);
+
+    match events.recv_timeout(timeout).unwrap().unwrap() {
+        db::Event::Created(doc) => assert_eq!((doc.id, doc.n, doc.version()), (id, 1, Some(0))),
+        other => panic!("expected `Created`, got {:?}", other),
+    }
+
+    match events.recv_timeout(timeout).unwrap().unwrap() {
+        db::Event::Updated { old, new } => {
+            assert_eq!((old.n, old.version()), (1, Some(0)));
+            assert_eq!((new.n, new.version()), (2, Some(1)));
+        }
+        other => panic!("expected `Updated`, got {:?}", other),
+    }
+
+    match events.recv_timeout(timeout).unwrap().unwrap() {
+        db::Event::Deleted(deleted) => assert_eq!(deleted, id),
+        other => panic!("expected `Deleted`, got {:?}", other),
+    }
+}
+
+#[test]
+fn dropped_channel_stops_watching() {
+    let dir = temp_dir();
+    let (store, db) = open_with_db(dir.path());
+
+    let changes = db.open_tree("notes/__changes__").unwrap();
+    let mut recorded = changes.watch_prefix(vec![]);
+
+    let events = store.watch().into_channel();
+
+    store.create(&Note::new("alpha", 1)).unwrap();
+    assert!(events.recv_timeout(Duration::from_secs(5)).is_ok());
+    assert!(matches!(
+        recorded.next_timeout(Duration::from_secs(5)),
+        Ok(sled::Event::Insert { .. })
+    ));
+
+    // Dropping the receiver stops the forwarding thread (and its `Watch`), even with no more
+    // events, so changes are no longer recorded.
+    drop(events);
+    std::thread::sleep(Duration::from_millis(500));
+
+    // Skip removals of earlier records.
+    while recorded.next_timeout(Duration::from_millis(100)).is_ok() {}
+
+    store.create(&Note::new("beta", 2)).unwrap();
+
+    assert!(recorded.next_timeout(Duration::from_millis(500)).is_err());
+}
+
+#[test]
+fn recorded_changes_are_removed() {
+    let dir = temp_dir();
+    let (store, db) = open_with_db(dir.path());
+
+    let changes = db.open_tree("notes/__changes__").unwrap();
+
+    let events = store.watch();
+
+    let ids = store.create_multi(&[Note::new("alpha", 1), Note::new("beta", 2)]).unwrap();
+    store.update_with(ids[0], |note| note.n = 3).unwrap();
+    store.delete(ids[1]).unwrap();
+
+    assert_eq!(events.take(4).count(), 4);
+    assert!(changes.is_empty());
+}